@@ -0,0 +1,107 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use std::fmt::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use obj::{FaceVertex, LoadConfig, Mtl, ObjData, ObjReader, Visitor};
+
+/// A grid of `n` by `n` quads split into triangles, with texture coordinates and normals.
+fn grid(n: usize) -> String {
+    let mut out = String::new();
+    for y in 0..=n {
+        for x in 0..=n {
+            let (u, v) = (x as f32 / n as f32, y as f32 / n as f32);
+            writeln!(out, "v {} {} {}", u * 10.0, (u * v).sin(), v * 10.0).unwrap();
+            writeln!(out, "vt {} {}", u, v).unwrap();
+            writeln!(out, "vn 0 1 0").unwrap();
+        }
+    }
+    writeln!(out, "g grid\nusemtl ground").unwrap();
+    for y in 0..n {
+        for x in 0..n {
+            let i = y * (n + 1) + x + 1;
+            let j = i + n + 1;
+            writeln!(out, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", i, i + 1, j + 1).unwrap();
+            writeln!(out, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", i, j + 1, j).unwrap();
+        }
+    }
+    out
+}
+
+/// Many copies of the materials of the Sponza scene under different names.
+fn materials(copies: usize) -> String {
+    let sponza = std::fs::read_to_string("test_assets/sponza.mtl").unwrap();
+    (0..copies)
+        .map(|i| sponza.replace("newmtl ", &format!("newmtl copy{}_", i)))
+        .collect()
+}
+
+struct Count(usize);
+
+impl Visitor for Count {
+    fn face(&mut self, face: &[FaceVertex]) {
+        self.0 += face.len();
+    }
+}
+
+fn load(c: &mut Criterion) {
+    let input = grid(300);
+    let mut group = c.benchmark_group("obj");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.sample_size(20);
+    group.bench_function("load_buf", |b| {
+        b.iter(|| ObjData::load_buf(black_box(input.as_bytes())).unwrap())
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("load_buf_parallel", |b| {
+        b.iter(|| ObjData::load_buf_parallel(black_box(input.as_bytes()), LoadConfig::default()).unwrap())
+    });
+    group.bench_function("reader", |b| {
+        b.iter(|| {
+            let mut reader = ObjReader::new(black_box(input.as_bytes()), LoadConfig::default());
+            let mut count = 0;
+            while let Some((_, statement)) = reader.next_statement() {
+                statement.unwrap();
+                count += 1;
+            }
+            count
+        })
+    });
+    group.bench_function("visit", |b| {
+        b.iter(|| {
+            let mut count = Count(0);
+            ObjReader::new(black_box(input.as_bytes()), LoadConfig::default())
+                .visit(&mut count)
+                .unwrap();
+            count.0
+        })
+    });
+    group.finish();
+
+    let input = materials(100);
+    let mut group = c.benchmark_group("mtl");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("reload", |b| {
+        b.iter(|| {
+            let mut mtl = Mtl::new("sponza.mtl".to_string());
+            mtl.reload(black_box(input.as_bytes())).unwrap();
+            mtl.materials.len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
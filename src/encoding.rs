@@ -0,0 +1,154 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Decoding of names and comments that aren't valid UTF-8.
+
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    io::{self, Write},
+    path::PathBuf,
+    str,
+};
+
+/// The first character of the private use range that holds undecodable bytes.
+const RAW_BASE: u32 = 0xEF00;
+
+/// How the text of statements is decoded.
+///
+/// Keywords and numbers are plain ASCII, so this only affects names, paths and comments. Legacy
+/// exporters often write these in a local 8-bit encoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameEncoding {
+    /// Decode UTF-8, replacing invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    #[default]
+    Utf8Lossy,
+    /// Decode every byte as the ISO-8859-1 character with the same value.
+    Latin1,
+    /// Decode UTF-8, keeping each byte of invalid sequences as a character in the range
+    /// `U+EF80..=U+EFFF`.
+    ///
+    /// Characters of that range in the file are kept as the characters of their UTF-8 bytes, so
+    /// the original bytes are always recovered with [`NameEncoding::encode`].
+    Raw,
+}
+
+impl NameEncoding {
+    /// Decode the bytes of a statement.
+    pub(crate) fn decode(self, bytes: Vec<u8>) -> String {
+        let bytes = match String::from_utf8(bytes) {
+            Ok(text) if self == NameEncoding::Utf8Lossy => return text,
+            Ok(text) if self == NameEncoding::Raw && !text.chars().any(|c| raw_byte(c).is_some()) => return text,
+            Ok(text) => text.into_bytes(),
+            Err(err) => err.into_bytes(),
+        };
+        match self {
+            NameEncoding::Utf8Lossy => String::from_utf8_lossy(&bytes).into_owned(),
+            NameEncoding::Latin1 => bytes.iter().map(|&b| char::from(b)).collect(),
+            NameEncoding::Raw => {
+                let mut text = String::with_capacity(bytes.len());
+                for chunk in bytes.utf8_chunks() {
+                    for c in chunk.valid().chars() {
+                        match raw_byte(c) {
+                            Some(_) => text.extend(c.encode_utf8(&mut [0; 4]).bytes().map(raw_char)),
+                            None => text.push(c),
+                        }
+                    }
+                    text.extend(chunk.invalid().iter().map(|&b| raw_char(b)));
+                }
+                text
+            }
+        }
+    }
+
+    /// Encode a name that was decoded with this policy back into the bytes of the file.
+    ///
+    /// Characters that can't be represented in Latin-1 are replaced with `?`.
+    pub fn encode(self, name: &str) -> Cow<'_, [u8]> {
+        match self {
+            NameEncoding::Utf8Lossy => Cow::Borrowed(name.as_bytes()),
+            NameEncoding::Latin1 if name.is_ascii() => Cow::Borrowed(name.as_bytes()),
+            NameEncoding::Latin1 => Cow::Owned(name.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect()),
+            NameEncoding::Raw => {
+                if !name.chars().any(|c| raw_byte(c).is_some()) {
+                    return Cow::Borrowed(name.as_bytes());
+                }
+                let mut bytes = Vec::with_capacity(name.len());
+                for c in name.chars() {
+                    match raw_byte(c) {
+                        Some(b) => bytes.push(b),
+                        None => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                    }
+                }
+                Cow::Owned(bytes)
+            }
+        }
+    }
+
+    /// The path named by a decoded name, which has the bytes of the name in the file on Unix.
+    pub(crate) fn path(self, name: &str) -> PathBuf {
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+            PathBuf::from(OsStr::from_bytes(&self.encode(name)))
+        }
+        #[cfg(not(unix))]
+        {
+            PathBuf::from(name)
+        }
+    }
+}
+
+/// A writer that encodes the text written to it like [`NameEncoding::encode`].
+pub(crate) struct EncodingWriter<W> {
+    out: W,
+    encoding: NameEncoding,
+}
+
+impl<W: Write> EncodingWriter<W> {
+    pub(crate) fn new(out: W, encoding: NameEncoding) -> Self {
+        EncodingWriter { out, encoding }
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Formatted text is written in whole strings, so a character is only split if bytes that
+        // aren't text are written, which are passed through.
+        let len = match str::from_utf8(buf) {
+            Ok(_) => buf.len(),
+            Err(err) if err.valid_up_to() > 0 => err.valid_up_to(),
+            Err(_) => return self.out.write(buf),
+        };
+        let text = str::from_utf8(&buf[..len]).expect("the bytes up to this length are valid UTF-8");
+        self.out.write_all(&self.encoding.encode(text))?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn raw_char(byte: u8) -> char {
+    // Bytes of invalid sequences are never ASCII, which keeps them within the private use area.
+    char::from_u32(RAW_BASE + u32::from(byte)).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn raw_byte(c: char) -> Option<u8> {
+    match u32::from(c).checked_sub(RAW_BASE) {
+        Some(b @ 0x80..=0xFF) => Some(b as u8),
+        _ => None,
+    }
+}
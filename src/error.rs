@@ -0,0 +1,103 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Errors of file based loading and saving.
+
+use std::{fmt, path::PathBuf};
+
+use crate::mtl::MtlError;
+use crate::obj::ObjError;
+
+/// The operation on a file that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Reading and parsing a file.
+    Load,
+    /// Writing a file.
+    Save,
+    /// Opening a file referenced by another one, such as a material library.
+    Resolve,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Load => write!(f, "load"),
+            Operation::Save => write!(f, "save"),
+            Operation::Resolve => write!(f, "resolve"),
+        }
+    }
+}
+
+/// Errors loading or saving `.obj` files and their material libraries.
+#[derive(Debug)]
+pub enum Error {
+    /// An operation on an `.obj` file failed.
+    Obj {
+        path: PathBuf,
+        operation: Operation,
+        error: ObjError,
+    },
+    /// An operation on an `.mtl` file failed.
+    Mtl {
+        path: PathBuf,
+        operation: Operation,
+        error: MtlError,
+    },
+    /// Some of the material libraries failed to load.
+    ///
+    /// The remaining libraries are still loaded and their materials are assigned.
+    MaterialLibraries(Vec<Error>),
+}
+
+impl Error {
+    pub(crate) fn obj(path: impl Into<PathBuf>, operation: Operation, error: impl Into<ObjError>) -> Self {
+        Error::Obj {
+            path: path.into(),
+            operation,
+            error: error.into(),
+        }
+    }
+
+    pub(crate) fn mtl(path: impl Into<PathBuf>, operation: Operation, error: impl Into<MtlError>) -> Self {
+        Error::Mtl {
+            path: path.into(),
+            operation,
+            error: error.into(),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Obj { error, .. } => Some(error),
+            Error::Mtl { error, .. } => Some(error),
+            Error::MaterialLibraries(errors) => errors.first().map(|e| e as _),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Obj { path, operation, .. } | Error::Mtl { path, operation, .. } => {
+                write!(f, "Failed to {} {}", operation, path.display())
+            }
+            Error::MaterialLibraries(errors) => {
+                write!(f, "{} material libraries failed to load", errors.len())
+            }
+        }
+    }
+}
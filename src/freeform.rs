@@ -0,0 +1,440 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! The free-form curve and surface geometry of .obj files as defined in the
+//! [full spec](http://paulbourke.net/dataformats/obj/).
+
+use std::{fmt, io::Write, str::FromStr};
+
+use crate::obj::{write_merging_group_change, IndexTuple, MergingGroup, ObjError, WriteToBuf};
+
+/// A vertex in the parameter space of a curve or surface, given by `vp u [v [w]]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamVertex(pub f32, pub Option<f32>, pub Option<f32>);
+
+impl fmt::Display for ParamVertex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        if let Some(v) = self.1 {
+            write!(f, " {}", v)?;
+            if let Some(w) = self.2 {
+                write!(f, " {}", w)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The type of curve or surface set by the `cstype` statement.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CurveType {
+    /// Basis matrix: `bmatrix`.
+    BasisMatrix,
+    /// Bezier: `bezier`.
+    Bezier,
+    /// B-spline: `bspline`.
+    BSpline,
+    /// Cardinal: `cardinal`.
+    Cardinal,
+    /// Taylor: `taylor`.
+    Taylor,
+}
+
+impl FromStr for CurveType {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "bmatrix" => Ok(CurveType::BasisMatrix),
+            "bezier" => Ok(CurveType::Bezier),
+            "bspline" => Ok(CurveType::BSpline),
+            "cardinal" => Ok(CurveType::Cardinal),
+            "taylor" => Ok(CurveType::Taylor),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for CurveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CurveType::BasisMatrix => "bmatrix",
+            CurveType::Bezier => "bezier",
+            CurveType::BSpline => "bspline",
+            CurveType::Cardinal => "cardinal",
+            CurveType::Taylor => "taylor",
+        })
+    }
+}
+
+/// The curve approximation technique set by the `ctech` statement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveTechnique {
+    /// Constant parametric subdivision with `res` segments between knots: `ctech cparm res`.
+    Parametric { res: f32 },
+    /// Spatial subdivision into segments of at most `max_length`: `ctech cspace maxlength`.
+    Spatial { max_length: f32 },
+    /// Curvature dependent subdivision: `ctech curv maxdist maxangle`.
+    ///
+    /// Segments deviate at most `max_distance` from the curve and their directions differ by at
+    /// most `max_angle` degrees.
+    Curvature { max_distance: f32, max_angle: f32 },
+}
+
+impl fmt::Display for CurveTechnique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveTechnique::Parametric { res } => write!(f, "cparm {}", res),
+            CurveTechnique::Spatial { max_length } => write!(f, "cspace {}", max_length),
+            CurveTechnique::Curvature {
+                max_distance,
+                max_angle,
+            } => write!(f, "curv {} {}", max_distance, max_angle),
+        }
+    }
+}
+
+/// The surface approximation technique set by the `stech` statement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceTechnique {
+    /// Constant parametric subdivision with separate resolutions for the u and v directions:
+    /// `stech cparma ures vres`.
+    ParametricA { res_u: f32, res_v: f32 },
+    /// Constant parametric subdivision with a single resolution for both directions:
+    /// `stech cparmb uvres`.
+    ParametricB { res: f32 },
+    /// Spatial subdivision into edges of at most `max_length`: `stech cspace maxlength`.
+    Spatial { max_length: f32 },
+    /// Curvature dependent subdivision: `stech curv maxdist maxangle`.
+    ///
+    /// Polygons deviate at most `max_distance` from the surface and their normals differ by at
+    /// most `max_angle` degrees.
+    Curvature { max_distance: f32, max_angle: f32 },
+}
+
+impl fmt::Display for SurfaceTechnique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurfaceTechnique::ParametricA { res_u, res_v } => write!(f, "cparma {} {}", res_u, res_v),
+            SurfaceTechnique::ParametricB { res } => write!(f, "cparmb {}", res),
+            SurfaceTechnique::Spatial { max_length } => write!(f, "cspace {}", max_length),
+            SurfaceTechnique::Curvature {
+                max_distance,
+                max_angle,
+            } => write!(f, "curv {} {}", max_distance, max_angle),
+        }
+    }
+}
+
+/// The free-form attribute state in effect when a curve or surface was declared.
+///
+/// Like materials, these are set by statements preceding the element and apply to all elements
+/// that follow until they are changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FreeFormAttributes {
+    /// Whether the `cstype` statement specified a rational type with the `rat` prefix.
+    pub rational: bool,
+    /// The curve or surface type set by `cstype`.
+    pub cstype: Option<CurveType>,
+    /// The degree in the u and, for surfaces, the v direction set by `deg`.
+    pub degree: Option<(usize, Option<usize>)>,
+    /// The basis matrix for the u direction set by `bmat u`.
+    pub basis_u: Option<Vec<f32>>,
+    /// The basis matrix for the v direction set by `bmat v`.
+    pub basis_v: Option<Vec<f32>>,
+    /// The step size in the u and, for surfaces, the v direction set by `step`.
+    pub step: Option<(f32, Option<f32>)>,
+    /// The curve approximation technique set by `ctech`.
+    pub curve_technique: Option<CurveTechnique>,
+    /// The surface approximation technique set by `stech`.
+    pub surface_technique: Option<SurfaceTechnique>,
+}
+
+impl FreeFormAttributes {
+    /// The number of attributes that have been set.
+    fn set_count(&self) -> usize {
+        [
+            self.cstype.is_some(),
+            self.degree.is_some(),
+            self.basis_u.is_some(),
+            self.basis_v.is_some(),
+            self.step.is_some(),
+            self.curve_technique.is_some(),
+            self.surface_technique.is_some(),
+        ]
+        .iter()
+        .filter(|&&set| set)
+        .count()
+    }
+
+    /// Write the statements needed to change the attribute state from `prev` to `self`.
+    fn write_changes<W: Write>(&self, prev: &FreeFormAttributes, out: &mut W) -> Result<(), ObjError> {
+        if self.cstype != prev.cstype || self.rational != prev.rational {
+            if let Some(cstype) = self.cstype {
+                let rat = if self.rational { "rat " } else { "" };
+                writeln!(out, "cstype {}{}", rat, cstype)?;
+            }
+        }
+        if self.degree != prev.degree {
+            match self.degree {
+                Some((u, Some(v))) => writeln!(out, "deg {} {}", u, v)?,
+                Some((u, None)) => writeln!(out, "deg {}", u)?,
+                None => {}
+            }
+        }
+        for (dir, basis, prev_basis) in [("u", &self.basis_u, &prev.basis_u), ("v", &self.basis_v, &prev.basis_v)] {
+            if basis != prev_basis {
+                if let Some(basis) = basis {
+                    write!(out, "bmat {}", dir)?;
+                    for value in basis {
+                        write!(out, " {}", value)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+        }
+        if self.step != prev.step {
+            match self.step {
+                Some((u, Some(v))) => writeln!(out, "step {} {}", u, v)?,
+                Some((u, None)) => writeln!(out, "step {}", u)?,
+                None => {}
+            }
+        }
+        if self.curve_technique != prev.curve_technique {
+            if let Some(technique) = self.curve_technique {
+                writeln!(out, "ctech {}", technique)?;
+            }
+        }
+        if self.surface_technique != prev.surface_technique {
+            if let Some(technique) = self.surface_technique {
+                writeln!(out, "stech {}", technique)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A reference to a segment of a 2D curve used by the `trim`, `hole` and `scrv` statements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveRef {
+    /// Starting and ending parameter values of the segment.
+    pub range: [f32; 2],
+    /// Index of the curve in `FreeForm::curves2`.
+    pub curve: usize,
+}
+
+/// The body statements appearing between a curve or surface statement and the matching `end`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FreeFormBody {
+    /// Global parameter values in the u direction set by `parm u`.
+    pub parm_u: Vec<f32>,
+    /// Global parameter values in the v direction set by `parm v`.
+    pub parm_v: Vec<f32>,
+    /// Outer trimming loops, each given by a `trim` statement.
+    pub trims: Vec<Vec<CurveRef>>,
+    /// Inner trimming loops, each given by a `hole` statement.
+    pub holes: Vec<Vec<CurveRef>>,
+    /// Special curves, each given by a `scrv` statement.
+    pub special_curves: Vec<Vec<CurveRef>>,
+    /// Special points given by `sp` as indices into `ObjData::parameter`.
+    pub special_points: Vec<usize>,
+}
+
+impl WriteToBuf for FreeFormBody {
+    type Error = ObjError;
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        for (dir, parm) in [("u", &self.parm_u), ("v", &self.parm_v)] {
+            if !parm.is_empty() {
+                write!(out, "parm {}", dir)?;
+                for value in parm {
+                    write!(out, " {}", value)?;
+                }
+                writeln!(out)?;
+            }
+        }
+        for (keyword, loops) in [
+            ("trim", &self.trims),
+            ("hole", &self.holes),
+            ("scrv", &self.special_curves),
+        ] {
+            for curves in loops {
+                write!(out, "{}", keyword)?;
+                for c in curves {
+                    write!(out, " {} {} {}", c.range[0], c.range[1], c.curve + 1)?;
+                }
+                writeln!(out)?;
+            }
+        }
+        if !self.special_points.is_empty() {
+            write!(out, "sp")?;
+            for idx in &self.special_points {
+                write!(out, " {}", idx + 1)?;
+            }
+            writeln!(out)?;
+        }
+        writeln!(out, "end")?;
+        Ok(())
+    }
+}
+
+/// A 3D curve given by `curv u0 u1 v1 v2 ...`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    /// The free-form attributes in effect for this curve.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values of the curve.
+    pub range: [f32; 2],
+    /// Control vertices as indices into `ObjData::position`.
+    pub vertices: Vec<usize>,
+    /// Statements between `curv` and `end`.
+    pub body: FreeFormBody,
+}
+
+/// A 2D curve in the parameter space of a surface given by `curv2 vp1 vp2 ...`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve2 {
+    /// The free-form attributes in effect for this curve.
+    pub attributes: FreeFormAttributes,
+    /// Control points as indices into `ObjData::parameter`.
+    pub vertices: Vec<usize>,
+    /// Statements between `curv2` and `end`.
+    pub body: FreeFormBody,
+}
+
+/// A surface given by `surf s0 s1 t0 t1 v1/vt1/vn1 ...`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    /// The free-form attributes in effect for this surface.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values in the u direction.
+    pub range_u: [f32; 2],
+    /// Starting and ending parameter values in the v direction.
+    pub range_v: [f32; 2],
+    /// Control vertices with optional texture and normal indices.
+    pub vertices: Vec<IndexTuple>,
+    /// Merging group in effect for this surface via the `mg ...` command.
+    pub merging_group: Option<MergingGroup>,
+    /// Statements between `surf` and `end`.
+    pub body: FreeFormBody,
+}
+
+/// Identifies the free-form element whose body statements are currently being parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum FreeFormElement {
+    Curve(usize),
+    Curve2(usize),
+    Surface(usize),
+}
+
+/// All free-form curves and surfaces of an `.obj` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FreeForm {
+    /// 3D curves declared with `curv`.
+    pub curves: Vec<Curve>,
+    /// 2D curves declared with `curv2`.
+    ///
+    /// These are referenced by the trimming loops and special curves of surfaces.
+    pub curves2: Vec<Curve2>,
+    /// Surfaces declared with `surf`.
+    pub surfaces: Vec<Surface>,
+}
+
+impl FreeForm {
+    /// Returns `true` if there are no curves or surfaces.
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty() && self.curves2.is_empty() && self.surfaces.is_empty()
+    }
+
+    fn attributes(&self, element: FreeFormElement) -> &FreeFormAttributes {
+        match element {
+            FreeFormElement::Curve(idx) => &self.curves[idx].attributes,
+            FreeFormElement::Curve2(idx) => &self.curves2[idx].attributes,
+            FreeFormElement::Surface(idx) => &self.surfaces[idx].attributes,
+        }
+    }
+
+    fn body(&self, element: FreeFormElement) -> &FreeFormBody {
+        match element {
+            FreeFormElement::Curve(idx) => &self.curves[idx].body,
+            FreeFormElement::Curve2(idx) => &self.curves2[idx].body,
+            FreeFormElement::Surface(idx) => &self.surfaces[idx].body,
+        }
+    }
+
+    pub(crate) fn body_mut(&mut self, element: FreeFormElement) -> &mut FreeFormBody {
+        match element {
+            FreeFormElement::Curve(idx) => &mut self.curves[idx].body,
+            FreeFormElement::Curve2(idx) => &mut self.curves2[idx].body,
+            FreeFormElement::Surface(idx) => &mut self.surfaces[idx].body,
+        }
+    }
+}
+
+impl WriteToBuf for FreeForm {
+    type Error = ObjError;
+    /// Serialize all curves and surfaces into the given writer.
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        // Attribute statements can only change the state but never unset it, so loading a file
+        // only ever increases the number of attributes that are set. Writing elements in that
+        // order thus reproduces a valid sequence of attribute statements.
+        let mut elements: Vec<_> = (0..self.curves2.len())
+            .map(FreeFormElement::Curve2)
+            .chain((0..self.curves.len()).map(FreeFormElement::Curve))
+            .chain((0..self.surfaces.len()).map(FreeFormElement::Surface))
+            .collect();
+        elements.sort_by_key(|&element| self.attributes(element).set_count());
+
+        let mut attributes = &FreeFormAttributes::default();
+        let mut merging_group = None;
+        for element in elements {
+            self.attributes(element).write_changes(attributes, out)?;
+            attributes = self.attributes(element);
+            if let FreeFormElement::Surface(idx) = element {
+                write_merging_group_change(merging_group, self.surfaces[idx].merging_group, out)?;
+                merging_group = self.surfaces[idx].merging_group;
+            }
+
+            match element {
+                FreeFormElement::Curve(idx) => {
+                    let curve = &self.curves[idx];
+                    write!(out, "curv {} {}", curve.range[0], curve.range[1])?;
+                    for idx in &curve.vertices {
+                        write!(out, " {}", idx + 1)?;
+                    }
+                }
+                FreeFormElement::Curve2(idx) => {
+                    write!(out, "curv2")?;
+                    for idx in &self.curves2[idx].vertices {
+                        write!(out, " {}", idx + 1)?;
+                    }
+                }
+                FreeFormElement::Surface(idx) => {
+                    let surface = &self.surfaces[idx];
+                    write!(
+                        out,
+                        "surf {} {} {} {}",
+                        surface.range_u[0], surface.range_u[1], surface.range_v[0], surface.range_v[1]
+                    )?;
+                    for idx in &surface.vertices {
+                        write!(out, " {}", idx)?;
+                    }
+                }
+            }
+            writeln!(out)?;
+            self.body(element).write_to_buf(out)?;
+        }
+        write_merging_group_change(merging_group, None, out)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Handling of the `call` and `csh` statements.
+
+use std::{
+    fmt,
+    io::Write,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::freeform::CurveRef;
+use crate::obj::{IndexTuple, LineTuple, ObjData, ObjError, Object, WriteToBuf};
+
+/// How `call` and `csh` statements are handled while loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IncludePolicy {
+    /// Skip `call` and `csh` statements.
+    Ignore,
+    /// Keep `call` and `csh` statements in [`ObjData::calls`] and [`ObjData::shell_commands`].
+    #[default]
+    Record,
+    /// Splice the files named by `call` statements into the loaded data.
+    ///
+    /// The files are opened by the resolver given to [`ObjData::load_buf_with_resolver`].
+    /// `csh` statements are recorded, but never executed. Called files may be nested at most
+    /// 32 levels deep.
+    Resolve,
+}
+
+/// The maximum nesting depth of called files.
+pub(crate) const MAX_INCLUDE_DEPTH: usize = 32;
+
+/// A file that is being loaded, which must not be called again by the files it calls.
+pub(crate) struct Include {
+    /// The file name as given by the `call` statement, or `None` for the file being loaded.
+    pub(crate) path: Option<PathBuf>,
+    /// The path by which the file is identified regardless of how it was named.
+    pub(crate) identity: PathBuf,
+}
+
+/// The data represented by the `call ...` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// Name of the `.obj` or `.mod` file to read.
+    pub filename: String,
+    /// Arguments substituted for `$1` through `$9` in the called file.
+    pub args: Vec<String>,
+}
+
+impl WriteToBuf for Call {
+    type Error = ObjError;
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        write!(out, "call {}", self.filename)?;
+        for arg in &self.args {
+            write!(out, " {}", arg)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+/// The data represented by the `csh ...` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCommand {
+    /// The command line to execute.
+    pub command: String,
+    /// Whether errors of the command should be ignored, which is written as `csh -command`.
+    pub ignore_errors: bool,
+}
+
+impl fmt::Display for ShellCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ignore_errors {
+            write!(f, "csh -{}", self.command)
+        } else {
+            write!(f, "csh {}", self.command)
+        }
+    }
+}
+
+/// Remove the `.` components of a path and the names followed by `..`, without accessing the file
+/// system.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Substitute the `$1` through `$9` placeholders of a called file by the given arguments.
+///
+/// Placeholders without a corresponding argument are replaced by an empty string.
+pub(crate) fn substitute_args(line: &str, args: &[String]) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().and_then(|n| n.to_digit(10))) {
+            ('$', Some(n)) if n > 0 => {
+                chars.next();
+                out.push_str(args.get(n as usize - 1).map_or("", String::as_str));
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+impl ObjData {
+    /// Append the vertex data and free-form elements of a called file to this one.
+    ///
+    /// All indices of `other` are shifted past the existing data. Its objects are returned with
+    /// rebased indices so that they can be spliced into the object stream by the caller.
+    pub(crate) fn splice(&mut self, other: ObjData, line_number: usize) -> Result<Vec<Object>, ObjError> {
+        let (p, t, n) = (self.position.len(), self.texture.len(), self.normal.len());
+        let (vp, c2) = (self.parameter.len(), self.free_form.curves2.len());
+        let tuple = |IndexTuple(pi, ti, ni): IndexTuple| IndexTuple(pi + p, ti.map(|ti| ti + t), ni.map(|ni| ni + n));
+        let curve_ref = |r: CurveRef| CurveRef {
+            range: r.range,
+            curve: r.curve + c2,
+        };
+
+        if !self.position.is_empty() && !other.position.is_empty() && self.color.is_empty() != other.color.is_empty() {
+            return Err(ObjError::InconsistentVertexColors { line_number });
+        }
+        if !self.position_w.is_empty() || !other.position_w.is_empty() {
+            self.position_w.resize(p, 1.0);
+            self.position_w.extend(other.position_w);
+            self.position_w.resize(p + other.position.len(), 1.0);
+        }
+        if !self.texture_w.is_empty() || !other.texture_w.is_empty() {
+            self.texture_w.resize(t, 0.0);
+            self.texture_w.extend(other.texture_w);
+            self.texture_w.resize(t + other.texture.len(), 0.0);
+        }
+        if !other.texture.is_empty() && (self.texture.is_empty() || other.texture_dim > self.texture_dim) {
+            self.texture_dim = other.texture_dim;
+        }
+        self.position.extend(other.position);
+        self.color.extend(other.color);
+        self.texture.extend(other.texture);
+        self.normal.extend(other.normal);
+        self.parameter.extend(other.parameter);
+
+        let mut free_form = other.free_form;
+        let bodies = free_form
+            .curves
+            .iter_mut()
+            .map(|c| &mut c.body)
+            .chain(free_form.curves2.iter_mut().map(|c| &mut c.body))
+            .chain(free_form.surfaces.iter_mut().map(|s| &mut s.body));
+        for body in bodies {
+            for refs in body
+                .trims
+                .iter_mut()
+                .chain(&mut body.holes)
+                .chain(&mut body.special_curves)
+            {
+                refs.iter_mut().for_each(|r| *r = curve_ref(*r));
+            }
+            body.special_points.iter_mut().for_each(|i| *i += vp);
+        }
+        for curve in &mut free_form.curves {
+            curve.vertices.iter_mut().for_each(|i| *i += p);
+        }
+        for curve in &mut free_form.curves2 {
+            curve.vertices.iter_mut().for_each(|i| *i += vp);
+        }
+        for surface in &mut free_form.surfaces {
+            surface.vertices.iter_mut().for_each(|v| *v = tuple(*v));
+        }
+        self.free_form.curves.extend(free_form.curves);
+        self.free_form.curves2.extend(free_form.curves2);
+        self.free_form.surfaces.extend(free_form.surfaces);
+
+        self.material_libs.extend(other.material_libs);
+        self.map_libs.extend(other.map_libs);
+        self.shadow_obj = other.shadow_obj.or_else(|| self.shadow_obj.take());
+        self.trace_obj = other.trace_obj.or_else(|| self.trace_obj.take());
+        self.calls.extend(other.calls);
+        self.shell_commands.extend(other.shell_commands);
+
+        let mut objects = other.objects;
+        for group in objects.iter_mut().flat_map(|o| &mut o.groups) {
+            for poly in &mut group.polys {
+                poly.0.iter_mut().for_each(|v| *v = tuple(*v));
+            }
+            for line in &mut group.lines {
+                for LineTuple(pi, ti) in &mut line.0 {
+                    *pi += p;
+                    *ti = ti.map(|ti| ti + t);
+                }
+            }
+            for point in &mut group.points {
+                point.0.iter_mut().for_each(|i| *i += p);
+            }
+        }
+        Ok(objects)
+    }
+}
@@ -0,0 +1,351 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Byte-level tokenizing and number parsing of statements.
+
+use std::{convert::TryFrom, str::FromStr};
+
+/// An iterator over the tokens of a statement, which are separated by ASCII whitespace.
+#[derive(Debug, Clone)]
+pub(crate) struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(line: &'a str) -> Self {
+        Tokens { rest: line }
+    }
+
+    /// The trimmed text following the tokens that were taken so far.
+    pub(crate) fn remainder(&self) -> &'a str {
+        self.rest.trim_ascii()
+    }
+
+    /// The tokens before a trailing comment, which starts with a token beginning with `#`.
+    pub(crate) fn before_comment(self) -> impl Iterator<Item = &'a str> + Clone {
+        self.take_while(|token| !token.starts_with('#'))
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let bytes = self.rest.as_bytes();
+        let start = match bytes.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(start) => start,
+            None => {
+                self.rest = "";
+                return None;
+            }
+        };
+        let end = bytes[start..]
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .map_or(bytes.len(), |len| start + len);
+        // Both ends are next to ASCII bytes, so they are on character boundaries.
+        let token = &self.rest[start..end];
+        self.rest = &self.rest[end..];
+        Some(token)
+    }
+}
+
+/// A number that is an argument of a statement.
+pub(crate) trait Number: Sized {
+    fn parse(token: &str) -> Option<Self>;
+}
+
+impl Number for f32 {
+    fn parse(token: &str) -> Option<Self> {
+        parse_f32(token)
+    }
+}
+
+macro_rules! impl_number {
+    ($($ty:ty),*) => {$(
+        impl Number for $ty {
+            fn parse(token: &str) -> Option<Self> {
+                FromStr::from_str(token).ok()
+            }
+        }
+    )*};
+}
+
+impl_number!(u32, usize);
+
+/// Parse a decimal integer exactly like `str::parse`, without going through `&str` pattern searches.
+pub(crate) fn parse_isize(token: &[u8]) -> Option<isize> {
+    let (negative, digits) = match token.split_first()? {
+        (b'-', rest) => (true, rest),
+        (b'+', rest) => (false, rest),
+        _ => (false, token),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut magnitude = 0usize;
+    for &b in digits {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        magnitude = magnitude.checked_mul(10)?.checked_add(usize::from(b - b'0'))?;
+    }
+    if negative {
+        0isize.checked_sub_unsigned(magnitude)
+    } else {
+        isize::try_from(magnitude).ok()
+    }
+}
+
+/// Split the `v/vt/vn` index tuple of a face into its position, texture and normal references.
+///
+/// A missing or malformed texture or normal reference is `None`, while the position reference is
+/// checked by the caller.
+pub(crate) fn parse_index_tuple(token: &str) -> (Option<isize>, Option<isize>, Option<isize>) {
+    let mut parts = token.as_bytes().split(|&b| b == b'/');
+    let p = parts.next().and_then(parse_isize);
+    let t = parts.next().and_then(parse_isize);
+    let n = parts.next().and_then(parse_isize);
+    (p, t, n)
+}
+
+/// Powers of ten that are exactly representable as `f32`.
+const POWERS_OF_TEN: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+
+/// Parse a decimal number into the nearest `f32`, exactly like `str::parse`.
+///
+/// Numbers whose digits fit into the mantissa of an `f32` and that have a small exponent, like the
+/// six or seven significant digits most exporters write, take a fast path. Everything else is left
+/// to the standard library.
+pub(crate) fn parse_f32(token: &str) -> Option<f32> {
+    parse_f32_fast(token.as_bytes()).or_else(|| token.parse().ok())
+}
+
+fn parse_f32_fast(token: &[u8]) -> Option<f32> {
+    let (negative, token) = match token.split_first()? {
+        (b'-', rest) => (true, rest),
+        (b'+', rest) => (false, rest),
+        _ => (false, token),
+    };
+
+    let mut mantissa = 0u64;
+    let mut digits = 0;
+    let mut exponent = 0i32;
+    let mut bytes = token.iter().peekable();
+    while let Some(&&b) = bytes.peek().filter(|b| b.is_ascii_digit()) {
+        mantissa = mantissa.wrapping_mul(10).wrapping_add(u64::from(b - b'0'));
+        digits += 1;
+        bytes.next();
+    }
+    if bytes.next_if_eq(&&b'.').is_some() {
+        while let Some(&&b) = bytes.peek().filter(|b| b.is_ascii_digit()) {
+            mantissa = mantissa.wrapping_mul(10).wrapping_add(u64::from(b - b'0'));
+            digits += 1;
+            exponent -= 1;
+            bytes.next();
+        }
+    }
+    // Longer mantissas may have overflowed.
+    if digits == 0 || digits > 19 {
+        return None;
+    }
+    if bytes.next_if(|&&b| b == b'e' || b == b'E').is_some() {
+        let sign = match bytes.next_if(|&&b| b == b'-' || b == b'+') {
+            Some(&b'-') => -1,
+            _ => 1,
+        };
+        let mut value = 0i32;
+        let mut exponent_digits = 0;
+        while let Some(&b) = bytes.next_if(|b| b.is_ascii_digit()) {
+            value = value * 10 + i32::from(b - b'0');
+            exponent_digits += 1;
+            if exponent_digits > 4 {
+                return None;
+            }
+        }
+        if exponent_digits == 0 {
+            return None;
+        }
+        exponent += sign * value;
+    }
+    if bytes.next().is_some() || mantissa > 1 << 24 {
+        return None;
+    }
+
+    // Both the mantissa and the power of ten are exact in `f32`, so the result of the single
+    // correctly rounded `f32` operation is the nearest `f32` to the decimal number.
+    let value = mantissa as f32;
+    let value = match exponent {
+        0 => value,
+        1..=10 => value * POWERS_OF_TEN[exponent as usize],
+        -10..=-1 => value / POWERS_OF_TEN[-exponent as usize],
+        _ => return None,
+    };
+    Some(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_exact(token: &str) {
+        let expected = token.parse::<f32>().ok().map(f32::to_bits);
+        assert_eq!(parse_f32(token).map(f32::to_bits), expected, "{:?}", token);
+    }
+
+    #[test]
+    fn tokens() {
+        let mut tokens = Tokens::new("  f 1/2/3\t4//5 \r");
+        assert_eq!(tokens.next(), Some("f"));
+        assert_eq!(tokens.remainder(), "1/2/3\t4//5");
+        assert_eq!(tokens.collect::<Vec<_>>(), ["1/2/3", "4//5"]);
+        assert_eq!(Tokens::new(" \t ").next(), None);
+    }
+
+    #[test]
+    fn parse_integers_like_std() {
+        let tokens = [
+            "0",
+            "-0",
+            "+7",
+            "-12",
+            "42",
+            "",
+            "-",
+            "+",
+            "1-",
+            "1 ",
+            "9223372036854775807",
+            "9223372036854775808",
+            "-9223372036854775808",
+            "-9223372036854775809",
+            "99999999999999999999999",
+        ];
+        for token in tokens.iter() {
+            assert_eq!(parse_isize(token.as_bytes()), token.parse().ok(), "{:?}", token);
+        }
+        assert_eq!(parse_index_tuple("3//-1"), (Some(3), None, Some(-1)));
+        assert_eq!(parse_index_tuple("x/2"), (None, Some(2), None));
+    }
+
+    #[test]
+    fn parse_floats_like_std() {
+        let tokens = [
+            "0",
+            "-0",
+            "+0",
+            "1",
+            "-1",
+            "0.5",
+            ".5",
+            "5.",
+            "-.5",
+            "1e5",
+            "1E-5",
+            "1.e5",
+            "1.5e+3",
+            "0.1",
+            "0.2",
+            "0.3",
+            "3.14159265358979",
+            "16777217",
+            "16777216.5",
+            "9007199254740993",
+            "1e22",
+            "1e23",
+            "1e-22",
+            "1e-23",
+            "123456789012345678",
+            "1234567890123456789",
+            "12345678901234567890",
+            "3.4028235e38",
+            "3.4028236e38",
+            "1e39",
+            "1e-45",
+            "1e-46",
+            "1.17549435e-38",
+            "1.012272298336029",
+            "16777215",
+            "16777217.0",
+            "1.6777217",
+            "0.0000000001",
+            "1234567e10",
+            "1234567e-11",
+            "inf",
+            "-inf",
+            "infinity",
+            "NaN",
+            "",
+            "-",
+            ".",
+            "e5",
+            "1e",
+            "1e+",
+            "1.2.3",
+            "1x",
+            "0x10",
+            "1e99999",
+            "1_0",
+            " 1",
+        ];
+        for token in tokens.iter() {
+            assert_exact(token);
+        }
+    }
+
+    #[test]
+    fn parse_random_floats_like_std() {
+        // A simple LCG is enough to cover many digit and exponent combinations.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state >> 33
+        };
+        for _ in 0..200_000 {
+            let digits = next() % 19 + 1;
+            let mantissa: String = (0..digits).map(|_| char::from(b'0' + (next() % 10) as u8)).collect();
+            let point = (next() % (digits + 1)) as usize;
+            let exponent = next() % 50;
+            let token = match next() % 3 {
+                0 => format!("{}.{}", &mantissa[..point], &mantissa[point..]),
+                1 => format!("-{}.{}e-{}", &mantissa[..point], &mantissa[point..], exponent),
+                _ => format!("{}e{}", mantissa, exponent),
+            };
+            assert_exact(&token);
+        }
+    }
+
+    #[test]
+    fn parse_floats_near_midpoints_like_std() {
+        // Decimals close to the midpoint between two neighboring `f32` values are the hardest to
+        // round correctly, especially with more digits than an `f32` holds.
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..100_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let value = f32::from_bits((state as u32) & 0x7f7f_ffff);
+            let next = f32::from_bits(value.to_bits() + 1);
+            let midpoint = (f64::from(value) + f64::from(next)) / 2.0;
+            for &candidate in &[midpoint, midpoint.next_down(), midpoint.next_up()] {
+                for digits in 6..18 {
+                    assert_exact(&format!("{:.*e}", digits, candidate));
+                }
+                assert_exact(&candidate.to_string());
+            }
+        }
+    }
+}
@@ -12,11 +12,35 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
+pub use self::encoding::NameEncoding;
+pub use self::error::{Error, Operation};
+pub use self::freeform::{
+    Curve, Curve2, CurveRef, CurveTechnique, CurveType, FreeForm, FreeFormAttributes, FreeFormBody, ParamVertex,
+    Surface, SurfaceTechnique,
+};
+pub use self::include::{Call, IncludePolicy, ShellCommand};
+pub use self::location::Location;
 pub use self::mtl::{Material, Mtl, MtlError, MtlMissingType};
 pub use self::obj::{
-    Group, IndexTuple, Line, LineTuple, LoadConfig, MtlLibsLoadError, Obj, ObjData, ObjError, ObjMaterial, Object,
-    SimplePolygon,
+    DisplayAttributes, Group, IndexKind, IndexTuple, Line, LineTuple, LoadConfig, MergingGroup, Obj, ObjData, ObjError,
+    ObjMaterial, Object, Point, PreservedLine, PreservedPosition, SimplePolygon, StatementHandler, TextureDim,
 };
+pub use self::reader::{ObjReader, Statement};
+pub use self::visit::{FaceVertex, Visitor};
+pub use self::warning::Warning;
 
+mod encoding;
+mod error;
+mod freeform;
+mod include;
+mod lex;
+mod lines;
+mod location;
 mod mtl;
 mod obj;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod reader;
+mod tessellate;
+mod visit;
+mod warning;
@@ -0,0 +1,180 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Reading of logical lines joined by backslash continuations.
+
+use std::{
+    io::{self, BufRead},
+    mem,
+};
+
+use crate::encoding::NameEncoding;
+
+/// The byte order mark that some exporters write at the start of UTF-8 files.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An iterator over the logical lines of an `.obj` or `.mtl` file.
+///
+/// A backslash at the end of a line continues the statement on the next line, unless the line is
+/// a comment, whose text may well end with a backslash like a Windows path. Each item is the
+/// 1-based number of the physical line where the statement begins together with its joined text.
+/// Lines are read as bytes and decoded with the given encoding, so text that isn't valid UTF-8
+/// doesn't fail the whole file.
+///
+/// Lines may end with `\n`, `\r\n` or a bare `\r` as written by classic Mac OS exporters. A byte
+/// order mark at the start of the input is skipped.
+pub(crate) struct LogicalLines<B> {
+    input: B,
+    encoding: NameEncoding,
+    line_number: usize,
+    /// The previous line ended with `\r`, so a `\n` following it belongs to the same terminator.
+    skip_lf: bool,
+    /// Whether a byte order mark may start the next line, which is only the case at the start of
+    /// the file.
+    bom: bool,
+}
+
+impl<B: BufRead> LogicalLines<B> {
+    pub(crate) fn new(input: B, encoding: NameEncoding) -> Self {
+        LogicalLines {
+            input,
+            encoding,
+            line_number: 0,
+            skip_lf: false,
+            bom: true,
+        }
+    }
+
+    /// Read lines of a file from somewhere after its start.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn continuing(input: B, encoding: NameEncoding) -> Self {
+        LogicalLines {
+            bom: false,
+            ..Self::new(input, encoding)
+        }
+    }
+
+    /// The number of physical lines read so far.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Append the next physical line without its terminator to `line`.
+    ///
+    /// Returns `false` at the end of the input.
+    fn read_line(&mut self, line: &mut Vec<u8>) -> io::Result<bool> {
+        let start = line.len();
+        let mut read = false;
+        loop {
+            let available = match self.input.fill_buf() {
+                Ok(available) => available,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if self.skip_lf {
+                self.skip_lf = false;
+                if available.first() == Some(&b'\n') {
+                    self.input.consume(1);
+                    continue;
+                }
+            }
+            if available.is_empty() {
+                break;
+            }
+            read = true;
+            let (used, done) = match available.iter().position(|&b| b == b'\n' || b == b'\r') {
+                Some(end) => {
+                    line.extend_from_slice(&available[..end]);
+                    self.skip_lf = available[end] == b'\r';
+                    (end + 1, true)
+                }
+                None => {
+                    line.extend_from_slice(available);
+                    (available.len(), false)
+                }
+            };
+            self.input.consume(used);
+            if done {
+                break;
+            }
+        }
+        if !read {
+            return Ok(false);
+        }
+        if self.bom && line[start..].starts_with(BOM) {
+            line.drain(start..start + BOM.len());
+        }
+        self.bom = false;
+        self.line_number += 1;
+        Ok(true)
+    }
+
+    /// Read the next statement into `line`, reusing its allocation.
+    ///
+    /// Returns the 1-based number of the line where the statement begins, or `None` at the end of
+    /// the input.
+    pub(crate) fn next_into(&mut self, line: &mut String) -> Option<(usize, io::Result<()>)> {
+        let mut bytes = mem::take(line).into_bytes();
+        bytes.clear();
+        let result = self.read_statement(&mut bytes);
+        *line = self.encoding.decode(bytes);
+        result
+    }
+
+    fn read_statement(&mut self, line: &mut Vec<u8>) -> Option<(usize, io::Result<()>)> {
+        match self.read_line(line) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => return Some((self.line_number + 1, Err(err))),
+        }
+        let start = self.line_number;
+        if line.trim_ascii_start().starts_with(b"#") {
+            return Some((start, Ok(())));
+        }
+
+        while let Some(len) = line.trim_ascii_end().strip_suffix(b"\\").map(<[u8]>::len) {
+            // The backslash is replaced by a space to keep the arguments on both lines apart.
+            line.truncate(len);
+            line.push(b' ');
+            match self.read_line(line) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(err) => return Some((start, Err(err))),
+            }
+        }
+        Some((start, Ok(())))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> LogicalLines<&'a [u8]> {
+    /// The input that is left to read, without the `\n` that ends a line ending with `\r\n`.
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        match self.input {
+            [b'\n', rest @ ..] if self.skip_lf => rest,
+            input => input,
+        }
+    }
+}
+
+impl<B: BufRead> Iterator for LogicalLines<B> {
+    type Item = (usize, io::Result<String>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        let (line_number, result) = self.next_into(&mut line)?;
+        Some((line_number, result.map(|()| line)))
+    }
+}
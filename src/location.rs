@@ -0,0 +1,113 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Source locations of loading errors and their rendering.
+
+use crate::lex::Tokens;
+
+use std::{
+    fmt::{self, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// The place in an `.obj` or `.mtl` file where an error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Path of the file, if it was loaded from one.
+    pub path: Option<PathBuf>,
+    /// 1-based number of the line where the statement begins.
+    pub line_number: usize,
+    /// Byte range of the offending token within `line`.
+    pub span: Range<usize>,
+    /// Text of the statement, with continued lines joined.
+    pub line: String,
+}
+
+impl Location {
+    pub(crate) fn new(path: Option<&Path>, line_number: usize, line: &str, span: Range<usize>) -> Self {
+        Location {
+            path: path.map(Path::to_owned),
+            line_number,
+            span,
+            line: line.to_string(),
+        }
+    }
+
+    /// 1-based column of the first character of the offending token.
+    pub fn column(&self) -> usize {
+        self.line[..self.span.start].chars().count() + 1
+    }
+
+    /// Write the statement with carets under the offending token.
+    fn write_snippet(&self, out: &mut String) -> fmt::Result {
+        let number = self.line_number.to_string();
+        let margin = " ".repeat(number.len());
+        let line = self.line.trim_end();
+        // Tabs are kept so that the carets line up with the statement.
+        let indent: String = self.line[..self.span.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(self.line[self.span.clone()].chars().count().max(1));
+
+        writeln!(out, "{} |", margin)?;
+        writeln!(out, "{} | {}", number, line)?;
+        write!(out, "{} | {}{}", margin, indent, carets)
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref path) = self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        write!(f, "{}:{}", self.line_number, self.column())
+    }
+}
+
+/// Render an error message followed by the annotated statement it refers to, if any.
+pub(crate) fn render(message: &dyn fmt::Display, location: Option<&Location>) -> String {
+    let mut out = String::new();
+    // Writing to a `String` never fails.
+    let _ = write!(out, "error: {}", message);
+    if let Some(location) = location {
+        let margin = " ".repeat(location.line_number.to_string().len());
+        let _ = write!(out, "\n{}--> {}\n", margin, location);
+        let _ = location.write_snippet(&mut out);
+    }
+    out
+}
+
+/// Split a statement into its whitespace separated tokens together with their byte ranges.
+pub(crate) fn tokens(line: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    Tokens::new(line).map(move |token| {
+        let start = token.as_ptr() as usize - line.as_ptr() as usize;
+        (start..start + token.len(), token)
+    })
+}
+
+/// The byte range of the arguments following the keyword of a statement.
+pub(crate) fn arguments_span(line: &str) -> Range<usize> {
+    let mut tokens = tokens(line);
+    let keyword = tokens.next().map_or(0..0, |(span, _)| span);
+    match tokens.last() {
+        Some((last, _)) => {
+            let start = line[keyword.end..].find(|c: char| !c.is_whitespace()).unwrap_or(0) + keyword.end;
+            start..last.end
+        }
+        // Point past the keyword when arguments are missing.
+        None => keyword.end..keyword.end,
+    }
+}
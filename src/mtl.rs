@@ -24,6 +24,12 @@ use std::{
     sync::Arc,
 };
 
+use crate::encoding::{EncodingWriter, NameEncoding};
+use crate::lex::{parse_f32, Tokens};
+use crate::lines::LogicalLines;
+use crate::location::{self, Location};
+use crate::warning::Warning;
+
 /// The model of an a single Material as defined in the .mtl spec.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Material {
@@ -115,12 +121,73 @@ pub enum MtlError {
     MissingMaterialName,
     /// Instruction requires a value, but that value was not provided.
     MissingValue(MtlMissingType),
+    /// An error of a statement together with its location in the source.
+    ///
+    /// The loader wraps all errors of statements in this variant. Use [`MtlError::kind`] to match
+    /// on the underlying error.
+    Located {
+        location: Location,
+        error: Box<MtlError>,
+    },
+}
+
+impl MtlError {
+    /// The underlying error, without the [`MtlError::Located`] wrapper if there is one.
+    pub fn kind(&self) -> &MtlError {
+        match self {
+            MtlError::Located { error, .. } => error,
+            _ => self,
+        }
+    }
+
+    /// The location of the statement that caused this error, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            MtlError::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Render this error together with a caret-annotated snippet of the failing statement.
+    pub fn render(&self) -> String {
+        location::render(self.kind(), self.location())
+    }
+
+    /// Attach the location of the statement on the given line to this error.
+    fn located(self, line_number: usize, line: &str) -> Self {
+        let span = match self {
+            MtlError::InvalidInstruction(_) => location::tokens(line).next().map(|(span, _)| span),
+            MtlError::InvalidValue(ref value) => location::tokens(line)
+                .skip(1)
+                .find(|(_, token)| token == value)
+                .map(|(span, _)| span),
+            _ => None,
+        };
+        MtlError::Located {
+            location: Location::new(
+                None,
+                line_number,
+                line,
+                span.unwrap_or_else(|| location::arguments_span(line)),
+            ),
+            error: Box::new(self),
+        }
+    }
+
+    /// Set the path of the file in which this error occurred, unless it is already known.
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        if let MtlError::Located { ref mut location, .. } = self {
+            location.path.get_or_insert_with(|| path.to_owned());
+        }
+        self
+    }
 }
 
 impl std::error::Error for MtlError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             MtlError::Io(err) => Some(err),
+            MtlError::Located { error, .. } => error.source(),
             _ => None,
         }
     }
@@ -134,6 +201,7 @@ impl fmt::Display for MtlError {
             MtlError::InvalidValue(val) => write!(f, "Attempted to parse the value '{}' but failed.", val),
             MtlError::MissingMaterialName => write!(f, "newmtl issued, but no name provided."),
             MtlError::MissingValue(ty) => write!(f, "Instruction is missing a value of type '{}'", ty),
+            MtlError::Located { location, error } => write!(f, "{}: {}", location, error),
         }
     }
 }
@@ -162,9 +230,9 @@ impl<'a, I: Iterator<Item = &'a str>> Parser<I> {
             }
         };
 
-        match (x.parse::<f32>(), y.parse::<f32>(), z.parse::<f32>()) {
-            (Ok(x), Ok(y), Ok(z)) => Ok([x, y, z]),
-            other => Err(MtlError::InvalidValue(format!("{:?}", other))),
+        match (parse_f32(x), parse_f32(y), parse_f32(z)) {
+            (Some(x), Some(y), Some(z)) => Ok([x, y, z]),
+            _ => Err(MtlError::InvalidValue(format!("{} {} {}", x, y, z))),
         }
     }
 
@@ -177,7 +245,7 @@ impl<'a, I: Iterator<Item = &'a str>> Parser<I> {
 
     fn get_f32(&mut self) -> Result<f32, MtlError> {
         match self.0.next() {
-            Some(v) => FromStr::from_str(v).map_err(|_| MtlError::InvalidValue(v.to_string())),
+            Some(v) => parse_f32(v).ok_or_else(|| MtlError::InvalidValue(v.to_string())),
             None => Err(MtlError::MissingValue(MtlMissingType::F32)),
         }
     }
@@ -210,6 +278,8 @@ pub struct Mtl {
     /// The individual materials are wrapped into an `Arc` to facilitate referencing this data
     /// where these materials are assigned in the `.obj` file.
     pub materials: Vec<Arc<Material>>,
+    /// How material names and texture paths are decoded when the library is loaded.
+    pub encoding: NameEncoding,
 }
 
 impl Mtl {
@@ -218,6 +288,7 @@ impl Mtl {
         Mtl {
             filename,
             materials: Vec::new(),
+            encoding: NameEncoding::default(),
         }
     }
 
@@ -236,133 +307,214 @@ impl Mtl {
     ///
     /// This function overwrites the contents of this library if it has already been loaded.
     pub fn reload(&mut self, input: impl Read) -> Result<&mut Self, MtlError> {
+        self.reload_impl(input, &mut |_| (), None)?;
+        Ok(self)
+    }
+
+    /// Load the mtl library from the given input buffer, passing the problems that were fixed up
+    /// while loading to the given function.
+    ///
+    /// This function overwrites the contents of this library if it has already been loaded.
+    pub fn reload_with_warning_fn(
+        &mut self,
+        input: impl Read,
+        mut warn: impl FnMut(Warning),
+    ) -> Result<&mut Self, MtlError> {
+        self.reload_impl(input, &mut warn, None)?;
+        Ok(self)
+    }
+
+    /// Load the mtl library from the given input buffer, skipping statements that fail to parse
+    /// instead of stopping at the first error.
+    ///
+    /// Returns all errors, while this library holds the materials loaded from the remaining
+    /// statements.
+    pub fn reload_recovering(&mut self, input: impl Read) -> Vec<MtlError> {
+        let mut errors = Vec::new();
+        self.reload_impl(input, &mut |_| (), Some(&mut errors))
+            .expect("errors are collected while recovering");
+        errors
+    }
+
+    fn reload_impl(
+        &mut self,
+        input: impl Read,
+        warn: &mut dyn FnMut(Warning),
+        mut errors: Option<&mut Vec<MtlError>>,
+    ) -> Result<(), MtlError> {
         self.materials.clear();
         let input = BufReader::new(input);
         let mut material = None;
-        for line in input.lines() {
-            let mut parser = match line {
-                Ok(ref line) => Parser(line.split_whitespace().filter(|s| !s.is_empty())),
-                Err(err) => return Err(MtlError::Io(err)),
-            };
-            match parser.0.next() {
-                Some("newmtl") => {
-                    self.materials.extend(material.take().map(Arc::new));
-                    material = Some(Material::new(
-                        parser.0.next().ok_or(MtlError::MissingMaterialName)?.to_string(),
-                    ));
-                }
-                Some("Ka") => {
-                    if let Some(ref mut m) = material {
-                        m.ka = Some(parser.get_vec()?);
+        let mut lines = LogicalLines::new(input, self.encoding);
+        let mut line = String::new();
+        while let Some((idx, result)) = lines.next_into(&mut line) {
+            if let Err(err) = result {
+                match errors {
+                    Some(ref mut errors) => {
+                        errors.push(MtlError::Io(err));
+                        break;
                     }
+                    None => return Err(MtlError::Io(err)),
                 }
-                Some("Kd") => {
-                    if let Some(ref mut m) = material {
-                        m.kd = Some(parser.get_vec()?);
-                    }
+            }
+            let mut parser = Parser(Tokens::new(&line));
+            let keyword = parser.0.next();
+            let result = self.parse_statement(keyword, parser, &mut material);
+            // Recognized statements are skipped until the first material is declared.
+            if result.is_ok() && material.is_none() {
+                if let Some(command) = keyword.filter(|k| !k.starts_with('#')) {
+                    warn(Warning::StatementOutsideMaterial {
+                        line_number: idx,
+                        command: command.to_string(),
+                    });
                 }
-                Some("Ks") => {
-                    if let Some(ref mut m) = material {
-                        m.ks = Some(parser.get_vec()?);
-                    }
+            }
+
+            if let Err(err) = result {
+                let err = err.located(idx, &line);
+                match errors {
+                    Some(ref mut errors) => errors.push(err),
+                    None => return Err(err),
                 }
-                Some("Ke") => {
-                    if let Some(ref mut m) = material {
-                        m.ke = Some(parser.get_vec()?);
-                    }
+            }
+        }
+
+        if let Some(material) = material {
+            self.materials.push(Arc::new(material));
+        }
+
+        Ok(())
+    }
+
+    /// Apply a statement of a material library to the material that is being declared.
+    fn parse_statement(
+        &mut self,
+        keyword: Option<&str>,
+        mut parser: Parser<Tokens<'_>>,
+        material: &mut Option<Material>,
+    ) -> Result<(), MtlError> {
+        match keyword {
+            Some("newmtl") => {
+                self.materials.extend(material.take().map(Arc::new));
+                *material = Some(Material::new(
+                    parser.0.next().ok_or(MtlError::MissingMaterialName)?.to_string(),
+                ));
+            }
+            Some("Ka") => {
+                if let Some(ref mut m) = material {
+                    m.ka = Some(parser.get_vec()?);
                 }
-                Some("Ns") => {
-                    if let Some(ref mut m) = material {
-                        m.ns = Some(parser.get_f32()?);
-                    }
+            }
+            Some("Kd") => {
+                if let Some(ref mut m) = material {
+                    m.kd = Some(parser.get_vec()?);
                 }
-                Some("Ni") => {
-                    if let Some(ref mut m) = material {
-                        m.ni = Some(parser.get_f32()?);
-                    }
+            }
+            Some("Ks") => {
+                if let Some(ref mut m) = material {
+                    m.ks = Some(parser.get_vec()?);
                 }
-                Some("Km") => {
-                    if let Some(ref mut m) = material {
-                        m.km = Some(parser.get_f32()?);
-                    }
+            }
+            Some("Ke") => {
+                if let Some(ref mut m) = material {
+                    m.ke = Some(parser.get_vec()?);
                 }
-                Some("d") => {
-                    if let Some(ref mut m) = material {
-                        m.d = Some(parser.get_f32()?);
-                    }
+            }
+            Some("Ns") => {
+                if let Some(ref mut m) = material {
+                    m.ns = Some(parser.get_f32()?);
                 }
-                Some("Tr") => {
-                    if let Some(ref mut m) = material {
-                        m.tr = Some(parser.get_f32()?);
-                    }
+            }
+            Some("Ni") => {
+                if let Some(ref mut m) = material {
+                    m.ni = Some(parser.get_f32()?);
                 }
-                Some("Tf") => {
-                    if let Some(ref mut m) = material {
-                        m.tf = Some(parser.get_vec()?);
-                    }
+            }
+            Some("Km") => {
+                if let Some(ref mut m) = material {
+                    m.km = Some(parser.get_f32()?);
                 }
-                Some("illum") => {
-                    if let Some(ref mut m) = material {
-                        m.illum = Some(parser.get_i32()?);
-                    }
+            }
+            Some("d") => {
+                if let Some(ref mut m) = material {
+                    m.d = Some(parser.get_f32()?);
                 }
-                Some("map_Ka") => {
-                    if let Some(ref mut m) = material {
-                        m.map_ka = Some(parser.into_string()?);
-                    }
+            }
+            Some("Tr") => {
+                if let Some(ref mut m) = material {
+                    m.tr = Some(parser.get_f32()?);
                 }
-                Some("map_Kd") => {
-                    if let Some(ref mut m) = material {
-                        m.map_kd = Some(parser.into_string()?);
-                    }
+            }
+            Some("Tf") => {
+                if let Some(ref mut m) = material {
+                    m.tf = Some(parser.get_vec()?);
                 }
-                Some("map_Ks") => {
-                    if let Some(ref mut m) = material {
-                        m.map_ks = Some(parser.into_string()?);
-                    }
+            }
+            Some("illum") => {
+                if let Some(ref mut m) = material {
+                    m.illum = Some(parser.get_i32()?);
                 }
-                Some("map_Ns") => {
-                    if let Some(ref mut m) = material {
-                        m.map_ns = Some(parser.into_string()?);
-                    }
+            }
+            Some("map_Ka") => {
+                if let Some(ref mut m) = material {
+                    m.map_ka = Some(parser.into_string()?);
                 }
-                Some("map_d") => {
-                    if let Some(ref mut m) = material {
-                        m.map_d = Some(parser.into_string()?);
-                    }
+            }
+            Some("map_Kd") => {
+                if let Some(ref mut m) = material {
+                    m.map_kd = Some(parser.into_string()?);
                 }
-                Some("map_refl") | Some("refl") => {
-                    if let Some(ref mut m) = material {
-                        m.map_refl = Some(parser.into_string()?);
-                    }
+            }
+            Some("map_Ks") => {
+                if let Some(ref mut m) = material {
+                    m.map_ks = Some(parser.into_string()?);
                 }
-                Some("map_bump") | Some("map_Bump") | Some("bump") => {
-                    if let Some(ref mut m) = material {
-                        m.map_bump = Some(parser.into_string()?);
-                    }
+            }
+            Some("map_Ns") => {
+                if let Some(ref mut m) = material {
+                    m.map_ns = Some(parser.into_string()?);
                 }
-                Some("map_disp") | Some("map_Disp") | Some("disp") => {
-                    if let Some(ref mut m) = material {
-                        m.map_disp = Some(parser.into_string()?);
-                    }
+            }
+            Some("map_d") => {
+                if let Some(ref mut m) = material {
+                    m.map_d = Some(parser.into_string()?);
                 }
-                Some(other) => {
-                    if !other.starts_with('#') {
-                        return Err(MtlError::InvalidInstruction(other.to_string()));
-                    }
+            }
+            Some("map_refl") | Some("refl") => {
+                if let Some(ref mut m) = material {
+                    m.map_refl = Some(parser.into_string()?);
+                }
+            }
+            Some("map_bump") | Some("map_Bump") | Some("bump") => {
+                if let Some(ref mut m) = material {
+                    m.map_bump = Some(parser.into_string()?);
                 }
-                None => {}
             }
+            Some("map_disp") | Some("map_Disp") | Some("disp") => {
+                if let Some(ref mut m) = material {
+                    m.map_disp = Some(parser.into_string()?);
+                }
+            }
+            Some(other) if !other.starts_with('#') => {
+                return Err(MtlError::InvalidInstruction(other.to_string()));
+            }
+            Some(_) | None => {}
         }
+        Ok(())
+    }
 
-        if let Some(material) = material {
-            self.materials.push(Arc::new(material));
+    /// Serialize the materials of this library into the given writer.
+    ///
+    /// The text is encoded with [`Mtl::encoding`].
+    pub fn write_to_buf(&self, out: &mut impl Write) -> Result<(), io::Error> {
+        match self.encoding {
+            NameEncoding::Utf8Lossy => self.write_text(out),
+            encoding => self.write_text(&mut EncodingWriter::new(out, encoding)),
         }
-
-        Ok(self)
     }
 
-    pub fn write_to_buf(&self, out: &mut impl Write) -> Result<(), io::Error> {
+    /// Write the materials as text, leaving their encoding to `out`.
+    fn write_text(&self, out: &mut impl Write) -> Result<(), io::Error> {
         for mtl in &self.materials {
             writeln!(out, "newmtl {}", mtl.name)?;
             if let Some([ka0, ka1, ka2]) = mtl.ka {
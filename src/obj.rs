@@ -17,21 +17,34 @@
 
 #[cfg(feature = "genmesh")]
 pub use genmesh::{Polygon, Quad, Triangle};
+#[cfg(feature = "memmap")]
+use memmap2::Mmap;
 
 use std::{
     collections::HashMap,
     fmt,
-    fs::File,
-    io::{self, BufRead, BufReader, Error, Read, Write},
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    ops::Range,
     path::{Path, PathBuf},
-    str::FromStr,
     sync::Arc,
 };
 
-use crate::mtl::{Material, Mtl, MtlError};
+use crate::encoding::{EncodingWriter, NameEncoding};
+use crate::error::{Error, Operation};
+use crate::freeform::{
+    Curve, Curve2, FreeForm, FreeFormAttributes, FreeFormBody, FreeFormElement, ParamVertex, Surface,
+};
+use crate::include::{self, Call, Include, IncludePolicy, ShellCommand, MAX_INCLUDE_DEPTH};
+use crate::location::{self, Location};
+use crate::mtl::{Material, Mtl};
+#[cfg(feature = "rayon")]
+use crate::parallel::ParallelReader;
+use crate::reader::{ObjReader, Statement, StatementSource};
+use crate::warning::Warning;
 use std::io::BufWriter;
 
-const DEFAULT_OBJECT: &str = "default";
+pub(crate) const DEFAULT_OBJECT: &str = "default";
 const DEFAULT_GROUP: &str = "default";
 
 /// Load configuration options.
@@ -45,11 +58,78 @@ pub struct LoadConfig {
     ///
     /// This is useful for loading `obj` files that have been extended with third-party commands.
     pub strict: bool,
+    /// Treat everything following `g` as the name of a single group.
+    ///
+    /// By default, `g name1 name2 ...` makes the following elements members of all the named
+    /// groups as required by the spec. Some exporters such as Blender write group names containing
+    /// spaces instead, which can be kept intact by setting this option to `true`.
+    pub single_group_name: bool,
+    /// How `call` and `csh` statements are handled.
+    ///
+    /// By default, they are recorded in [`ObjData::calls`] and [`ObjData::shell_commands`].
+    pub include: IncludePolicy,
+    /// Keep comments and unrecognized statements so that they are written back in place.
+    ///
+    /// Unrecognized statements are only kept if they are not rejected by strict mode. Lines
+    /// preceding the first group are kept in [`ObjData::header`], which then replaces the header
+    /// comment added by the writer.
+    pub preserve_lines: bool,
+    /// Allow elements to reference vertices that are declared later in the file.
+    ///
+    /// Positive indices past the data declared so far are then only checked once the whole file
+    /// has been loaded, and errors of these checks carry no [`Location`]. Negative relative
+    /// indices always refer to previously declared data.
+    pub forward_references: bool,
+    /// How names, paths and comments are decoded.
+    ///
+    /// The material libraries named by `mtllib` are decoded the same way, see [`Mtl::encoding`].
+    pub encoding: NameEncoding,
 }
 
 impl Default for LoadConfig {
     fn default() -> Self {
-        LoadConfig { strict: true }
+        LoadConfig {
+            strict: true,
+            single_group_name: false,
+            include: IncludePolicy::default(),
+            preserve_lines: false,
+            forward_references: false,
+            encoding: NameEncoding::default(),
+        }
+    }
+}
+
+/// A hook for statements that the loader doesn't recognize.
+///
+/// This allows extensions of the `.obj` format to be loaded alongside the standard statements,
+/// e.g. by collecting custom data in the handler itself.
+pub trait StatementHandler {
+    /// Handle an unrecognized statement.
+    ///
+    /// The handler is given the line number, the keyword and the arguments of the statement, as
+    /// well as the object being loaded and its current group, if any. Returning `Ok(false)` leaves
+    /// the statement to the loader, which rejects it in strict mode and skips it otherwise.
+    fn handle(
+        &mut self,
+        line_number: usize,
+        keyword: &str,
+        args: &[&str],
+        object: &mut Object,
+        group: Option<&mut Group>,
+    ) -> Result<bool, ObjError>;
+}
+
+/// The handler used when none is given, which leaves all statements to the loader.
+impl StatementHandler for () {
+    fn handle(
+        &mut self,
+        _: usize,
+        _: &str,
+        _: &[&str],
+        _: &mut Object,
+        _: Option<&mut Group>,
+    ) -> Result<bool, ObjError> {
+        Ok(false)
     }
 }
 
@@ -83,6 +163,12 @@ pub struct SimplePolygon(pub Vec<IndexTuple>);
 #[derive(Debug, Clone, Hash, PartialEq)]
 pub struct Line(pub Vec<LineTuple>);
 
+/// A point element with arbitrary many vertices.
+///
+/// Each vertex is given by its position index. Points do not support texture coordinates or normals.
+#[derive(Debug, Clone, Hash, PartialEq)]
+pub struct Point(pub Vec<usize>);
+
 pub trait WriteToBuf {
     type Error: std::fmt::Display;
     fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), Self::Error>;
@@ -143,6 +229,18 @@ impl WriteToBuf for Line {
     }
 }
 
+impl WriteToBuf for Point {
+    type Error = ObjError;
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        write!(out, "p")?;
+        for idx in &self.0 {
+            write!(out, " {}", idx + 1)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
 #[cfg(feature = "genmesh")]
 impl SimplePolygon {
     /// Convert a `SimplePolygon` into a `genmesh` `Polygon` of `IndexTuple`s.
@@ -197,21 +295,159 @@ pub enum ObjError {
     ZeroVertexNumber {
         line_number: usize,
     },
+    /// An index refers to an item past the end of the declared data, or before the first item
+    /// in case of a negative relative index.
+    IndexOutOfRange {
+        line_number: usize,
+        kind: IndexKind,
+        index: isize,
+        len: usize,
+    },
     /// Lines do not support normal indexes.
     LineHasNormalIndex {
         line_number: usize,
     },
+    /// A free-form body statement such as `parm` or `end` appeared outside of a `curv`, `curv2`
+    /// or `surf` element.
+    FreeFormStatementOutsideElement {
+        line_number: usize,
+        command: String,
+    },
+    /// Vertex colors must be given either on all `v` lines or on none of them.
+    InconsistentVertexColors {
+        line_number: usize,
+    },
+    /// A free-form surface could not be tessellated, e.g. because its control vertices don't
+    /// match its degree and parameter values.
+    TessellationFailure {
+        surface: usize,
+        message: String,
+    },
+    /// A file named by a `call` statement could not be opened or loaded.
+    IncludeFailure {
+        line_number: usize,
+        filename: String,
+        error: Box<ObjError>,
+    },
+    /// A file named by a `call` statement directly or indirectly calls itself.
+    IncludeCycle {
+        line_number: usize,
+        filename: String,
+    },
+    /// A file named by a `call` statement is nested more than 32 levels deep.
+    IncludeTooDeep {
+        line_number: usize,
+        filename: String,
+    },
     /// [`genmesh::Polygon`] only supports triangles and squares.
     #[cfg(feature = "genmesh")]
     GenMeshWrongNumberOfVertsInPolygon {
         vert_count: usize,
     },
+    /// An error of a statement together with its location in the source.
+    ///
+    /// The loaders wrap all errors of statements in this variant. Use [`ObjError::kind`] to match
+    /// on the underlying error.
+    Located {
+        location: Location,
+        error: Box<ObjError>,
+    },
+}
+
+impl ObjError {
+    /// The underlying error, without the [`ObjError::Located`] wrapper if there is one.
+    pub fn kind(&self) -> &ObjError {
+        match self {
+            ObjError::Located { error, .. } => error,
+            _ => self,
+        }
+    }
+
+    /// The location of the statement that caused this error, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            ObjError::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Render this error together with a caret-annotated snippet of the failing statement.
+    ///
+    /// ```text
+    /// error: Zero vertex numbers are invalid. (line: 3)
+    ///  --> model.obj:3:3
+    ///   |
+    /// 3 | f 0 1 2
+    ///   |   ^
+    /// ```
+    pub fn render(&self) -> String {
+        location::render(self.kind(), self.location())
+    }
+
+    /// Attach the location of the statement on the given line to this error.
+    ///
+    /// Errors that are already located only get the path, if it isn't known yet.
+    pub(crate) fn located(self, path: Option<&Path>, line_number: usize, line: &str) -> Self {
+        match (self, path) {
+            (err @ ObjError::Located { .. }, Some(path)) => err.with_path(path),
+            (err @ ObjError::Located { .. }, None) => err,
+            (err, path) => {
+                let span = err.span(line);
+                ObjError::Located {
+                    location: Location::new(path, line_number, line, span),
+                    error: Box::new(err),
+                }
+            }
+        }
+    }
+
+    /// Set the path of the file in which this error occurred, unless it is already known.
+    fn with_path(mut self, path: &Path) -> Self {
+        if let ObjError::Located { ref mut location, .. } = self {
+            location.path.get_or_insert_with(|| path.to_owned());
+        }
+        self
+    }
+
+    /// Find the byte range of the token of a statement that caused this error.
+    fn span(&self, line: &str) -> Range<usize> {
+        let mut arguments = location::tokens(line).skip(1);
+        // The component of an index tuple that refers to the given kind of data.
+        let component = |token: &str, kind| {
+            let n = match kind {
+                IndexKind::Texture => 1,
+                IndexKind::Normal => 2,
+                _ => 0,
+            };
+            token.split('/').nth(n).and_then(|i| i.parse::<isize>().ok())
+        };
+        let found = match self {
+            ObjError::MalformedFaceGroup { group, .. } => arguments.find(|(_, token)| token == group),
+            ObjError::UnexpectedCommand { .. } | ObjError::FreeFormStatementOutsideElement { .. } => {
+                location::tokens(line).next()
+            }
+            ObjError::ZeroVertexNumber { .. } => {
+                arguments.find(|(_, token)| component(token, IndexKind::Position) == Some(0))
+            }
+            ObjError::IndexOutOfRange { kind, index, .. } => {
+                arguments.find(|(_, token)| component(token, *kind) == Some(*index))
+            }
+            ObjError::LineHasNormalIndex { .. } => arguments.find(|(_, token)| token.split('/').nth(2).is_some()),
+            ObjError::IncludeFailure { filename, .. }
+            | ObjError::IncludeCycle { filename, .. }
+            | ObjError::IncludeTooDeep { filename, .. } => arguments.find(|(_, token)| token == filename),
+            _ => None,
+        };
+        found.map_or_else(|| location::arguments_span(line), |(span, _)| span)
+    }
 }
 
 impl std::error::Error for ObjError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             ObjError::Io(err) => Some(err),
+            ObjError::IncludeFailure { error, .. } => Some(error.as_ref()),
+            ObjError::Located { error, .. } => error.source(),
             _ => None,
         }
     }
@@ -244,52 +480,74 @@ impl fmt::Display for ObjError {
             ObjError::ZeroVertexNumber { line_number } => {
                 write!(f, "Zero vertex numbers are invalid. (line: {})", line_number)
             }
+            ObjError::IndexOutOfRange {
+                line_number,
+                kind,
+                index,
+                len,
+            } => write!(
+                f,
+                "Index is out of range. (line: {}, {} index: {}, count: {})",
+                line_number, kind, index, len
+            ),
             ObjError::LineHasNormalIndex { line_number } => {
                 write!(f, "Lines with normals are invalid. (line: {})", line_number)
             }
+            ObjError::FreeFormStatementOutsideElement { line_number, command } => write!(
+                f,
+                "Free-form statement found outside of a curve or surface. (line: {}, command: {})",
+                line_number, command
+            ),
+            ObjError::InconsistentVertexColors { line_number } => write!(
+                f,
+                "Vertex colors must be specified for all vertices or for none. (line: {})",
+                line_number
+            ),
+            ObjError::TessellationFailure { surface, message } => {
+                write!(f, "Failed to tessellate surface. (surface: {}, {})", surface, message)
+            }
+            ObjError::IncludeFailure {
+                line_number, filename, ..
+            } => write!(
+                f,
+                "Failed to load called file. (line: {}, file: {})",
+                line_number, filename
+            ),
+            ObjError::IncludeCycle { line_number, filename } => write!(
+                f,
+                "Called file includes itself. (line: {}, file: {})",
+                line_number, filename
+            ),
+            ObjError::IncludeTooDeep { line_number, filename } => write!(
+                f,
+                "Called files are nested too deeply. (line: {}, file: {})",
+                line_number, filename
+            ),
             #[cfg(feature = "genmesh")]
             ObjError::GenMeshWrongNumberOfVertsInPolygon { vert_count } => write!(
                 f,
                 "[`genmesh::Polygon`] only supports triangles and squares. (vertex count: {}",
                 vert_count
             ),
+            ObjError::Located { location, error } => write!(f, "{}: {}", location, error),
         }
     }
 }
 
 impl From<io::Error> for ObjError {
-    fn from(e: Error) -> Self {
+    fn from(e: io::Error) -> Self {
         Self::Io(e)
     }
 }
 
-/// Error loading individual material libraries.
-///
-/// The `Vec` items are tuples with first component being the the .mtl file, and the second its
-/// corresponding error.
-#[derive(Debug)]
-pub struct MtlLibsLoadError(pub Vec<(String, MtlError)>);
-
-impl std::error::Error for MtlLibsLoadError {}
-
-impl fmt::Display for MtlLibsLoadError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "One of the material libraries failed to load: {:?}", self.0)
-    }
-}
-
-impl From<Vec<(String, MtlError)>> for MtlLibsLoadError {
-    fn from(e: Vec<(String, MtlError)>) -> Self {
-        MtlLibsLoadError(e)
-    }
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct Object {
     /// Name of the object assigned by the `o ...` command in the `.obj` file.
     pub name: String,
     /// Groups belonging to this object.
     pub groups: Vec<Group>,
+    /// Comments and unrecognized lines following the `o ...` command outside of any group.
+    pub preserved: Vec<String>,
 }
 
 impl Object {
@@ -297,6 +555,7 @@ impl Object {
         Object {
             name,
             groups: Vec::new(),
+            preserved: Vec::new(),
         }
     }
 }
@@ -308,9 +567,15 @@ impl WriteToBuf for Object {
         if self.name.as_str() != DEFAULT_OBJECT {
             writeln!(out, "o {}", self.name)?;
         }
+        for line in &self.preserved {
+            writeln!(out, "{}", line)?;
+        }
 
+        let mut merging_group = None;
         let mut group_iter = self.groups.iter().peekable();
         while let Some(group) = group_iter.next() {
+            write_merging_group_change(merging_group, group.merging_group, out)?;
+            merging_group = group.merging_group;
             group.write_to_buf(out)?;
 
             // Below we check that groups with `index > 0` have the same name as their predecessors
@@ -320,6 +585,7 @@ impl WriteToBuf for Object {
                 .map(|next_group| next_group.index == 0 || next_group.name == group.name)
                 .unwrap_or(true));
         }
+        write_merging_group_change(merging_group, None, out)?;
 
         Ok(())
     }
@@ -346,10 +612,96 @@ impl ObjMaterial {
     }
 }
 
+/// The data represented by the `mg ...` command.
+///
+/// Surfaces and faces in the same merging group are merged along shared edges, with `resolution`
+/// being the maximum distance between two surfaces that will be merged together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergingGroup {
+    /// Number of the merging group, which is never 0 since `mg 0` turns merging off.
+    pub id: u32,
+    /// Maximum distance between surfaces that are merged.
+    pub resolution: f32,
+}
+
+/// Display and render attributes assigned by the `bevel`, `c_interp`, `d_interp`, `lod` and
+/// `usemap` commands in the `.obj` file.
+///
+/// Attributes that are not set are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayAttributes {
+    /// Bevel interpolation set by `bevel on|off`.
+    pub bevel: Option<bool>,
+    /// Color interpolation set by `c_interp on|off`.
+    pub color_interpolation: Option<bool>,
+    /// Dissolve interpolation set by `d_interp on|off`.
+    pub dissolve_interpolation: Option<bool>,
+    /// Level of detail set by `lod level`.
+    pub lod: Option<u32>,
+    /// Texture map set by `usemap name`, or `Some(None)` after `usemap off`.
+    pub texture_map: Option<Option<String>>,
+}
+
+impl WriteToBuf for DisplayAttributes {
+    type Error = ObjError;
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        let switch = |on: bool| if on { "on" } else { "off" };
+        if let Some(on) = self.bevel {
+            writeln!(out, "bevel {}", switch(on))?;
+        }
+        if let Some(on) = self.color_interpolation {
+            writeln!(out, "c_interp {}", switch(on))?;
+        }
+        if let Some(on) = self.dissolve_interpolation {
+            writeln!(out, "d_interp {}", switch(on))?;
+        }
+        if let Some(level) = self.lod {
+            writeln!(out, "lod {}", level)?;
+        }
+        match self.texture_map {
+            Some(Some(ref name)) => writeln!(out, "usemap {}", name)?,
+            Some(None) => writeln!(out, "usemap off")?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Where a preserved line is written among the statements of a group.
+///
+/// The variants are ordered like the statements are written.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, PartialOrd, Eq, Ord)]
+pub enum PreservedPosition {
+    /// After the `g` statement, before the material, smoothing group and display attributes.
+    Start,
+    /// Before the polygon with the given index.
+    Poly(usize),
+    /// Before the line with the given index.
+    Line(usize),
+    /// Before the point with the given index.
+    Point(usize),
+    /// After all polygons, lines and points.
+    End,
+}
+
+/// A comment or unrecognized line kept by the loader within a group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreservedLine {
+    /// The statement of the group that followed this line in the file.
+    pub position: PreservedPosition,
+    /// The text of the line.
+    pub text: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Group {
     /// Name of the group assigned by the `g ...` command in the `.obj` file.
+    ///
+    /// This is the first name if the command lists several groups.
     pub name: String,
+    /// Names of any further groups listed by the `g ...` command, which the elements of this
+    /// group belong to as well.
+    pub additional_names: Vec<String>,
     /// An index is used to tell groups apart that share the same name.
     ///
     /// This doesn't appear explicitly in the `.obj` file, but is used here to simplify groups by
@@ -359,24 +711,132 @@ pub struct Group {
     ///
     /// After material libs are loaded, this will point to the loaded `Material` struct.
     pub material: Option<ObjMaterial>,
+    /// Smoothing group assigned to this group via the `s ...` command in the `.obj` file.
+    ///
+    /// Both `s off` and `s 0` are stored as `Some(0)`, which turns smoothing off.
+    pub smoothing_group: Option<u32>,
+    /// Merging group in effect for the elements of this group via the `mg ...` command in the
+    /// `.obj` file.
+    pub merging_group: Option<MergingGroup>,
+    /// Display and render attributes in effect for the elements of this group.
+    pub display: DisplayAttributes,
     /// A list of polygons appearing as `f ...` in the `.obj` file.
     pub polys: Vec<SimplePolygon>,
     /// A list of lines appearing as `l ...` in the `.obj` file.
     pub lines: Vec<Line>,
+    /// A list of points appearing as `p ...` in the `.obj` file.
+    pub points: Vec<Point>,
+    /// Comments and unrecognized lines kept among the statements of this group.
+    pub preserved: Vec<PreservedLine>,
 }
 
 impl Group {
     pub fn new(name: String) -> Self {
         Group {
             name,
+            additional_names: Vec::new(),
             index: 0,
             material: None,
+            smoothing_group: None,
+            merging_group: None,
+            display: DisplayAttributes::default(),
             polys: Vec::new(),
             lines: Vec::new(),
+            points: Vec::new(),
+            preserved: Vec::new(),
+        }
+    }
+
+    /// Names of all groups that the elements of this group belong to.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        Some(self.name.as_str())
+            .into_iter()
+            .chain(self.additional_names.iter().map(String::as_str))
+    }
+
+    /// Whether any polygons, lines or points belong to this group.
+    fn has_geometry(&self) -> bool {
+        !self.polys.is_empty() || !self.lines.is_empty() || !self.points.is_empty()
+    }
+
+    /// Keep the lines that are waiting for the next statement of this group at the given position.
+    fn keep_lines(&mut self, pending: &mut Vec<String>, position: PreservedPosition) {
+        self.preserved
+            .extend(pending.drain(..).map(|text| PreservedLine { position, text }));
+    }
+
+    /// Get the group that elements following a state change such as `usemtl` should be added to.
+    ///
+    /// A group that already has geometry is pushed to `object` and continued by a new group with
+    /// the same name and state, but the next index.
+    fn split(group: Option<Group>, object: &mut Object) -> Group {
+        let mut g = group.unwrap_or_else(|| Group::new(DEFAULT_GROUP.to_string()));
+        if g.has_geometry() {
+            object.groups.push(g.clone());
+            g.index += 1;
+            g.polys.clear();
+            g.lines.clear();
+            g.points.clear();
+            g.preserved.clear();
+        }
+        g
+    }
+
+    /// Update the display attributes of the current group.
+    ///
+    /// Like materials, display attributes apply to whole groups.
+    fn set_display(group: &mut Option<Group>, object: &mut Object, update: impl FnOnce(&mut DisplayAttributes)) {
+        let mut display = group.as_ref().map(|g| g.display.clone()).unwrap_or_default();
+        update(&mut display);
+        if group.as_ref().map(|g| &g.display) != Some(&display) {
+            let mut g = Group::split(group.take(), object);
+            g.display = display;
+            *group = Some(g);
+        }
+    }
+
+    /// Get the group that a new element is added to.
+    ///
+    /// Unlike materials, the merging group is a global state, so the current group is only split
+    /// once it is followed by an element. The current group is only moved when it's split.
+    fn for_element<'a>(
+        group: &'a mut Option<Group>,
+        object: &mut Object,
+        merging_group: Option<MergingGroup>,
+    ) -> &'a mut Group {
+        if group.as_ref().map(|g| g.merging_group) != Some(merging_group) {
+            let g = match group.take() {
+                Some(g) => Group::split(Some(g), object),
+                None => Group::new(DEFAULT_GROUP.to_string()),
+            };
+            *group = Some(Group { merging_group, ..g });
         }
+        group.as_mut().expect("the current group is set above")
     }
 }
 
+impl WriteToBuf for MergingGroup {
+    type Error = ObjError;
+    fn write_to_buf<W: Write>(&self, out: &mut W) -> Result<(), ObjError> {
+        writeln!(out, "mg {} {}", self.id, self.resolution)?;
+        Ok(())
+    }
+}
+
+/// Write the `mg ...` command needed to change the merging group from `prev` to `next`.
+pub(crate) fn write_merging_group_change<W: Write>(
+    prev: Option<MergingGroup>,
+    next: Option<MergingGroup>,
+    out: &mut W,
+) -> Result<(), ObjError> {
+    match next {
+        _ if prev == next => {}
+        Some(ref mg) => mg.write_to_buf(out)?,
+        None => writeln!(out, "mg off")?,
+    }
+    Ok(())
+}
+
 impl WriteToBuf for Group {
     type Error = ObjError;
     /// Serialize this `Group` into the given writer.
@@ -384,53 +844,151 @@ impl WriteToBuf for Group {
         // When index is greater than 0, we know that this group is the same as the previous group,
         // so don't bother declaring a new one.
         if self.index == 0 {
-            writeln!(out, "g {}", self.name)?;
+            writeln!(out, "g {}", self.names().collect::<Vec<_>>().join(" "))?;
         }
 
+        // The lines are kept in the order of the file, in which elements of different kinds may
+        // be mixed. The stable sort keeps the order of the lines before the same statement.
+        let mut preserved: Vec<&PreservedLine> = self.preserved.iter().collect();
+        preserved.sort_by_key(|line| line.position);
+        let mut preserved = preserved.into_iter().peekable();
+        let mut write_preserved = |out: &mut W, position: PreservedPosition| -> Result<(), ObjError> {
+            while let Some(line) = preserved.next_if(|line| line.position <= position) {
+                writeln!(out, "{}", line.text)?;
+            }
+            Ok(())
+        };
+
+        write_preserved(out, PreservedPosition::Start)?;
         match self.material {
             Some(ObjMaterial::Ref(ref name)) => writeln!(out, "usemtl {}", name)?,
             Some(ObjMaterial::Mtl(ref mtl)) => writeln!(out, "usemtl {}", mtl.name)?,
             None => {}
         }
 
-        for poly in &self.polys {
+        match self.smoothing_group {
+            Some(0) => writeln!(out, "s off")?,
+            Some(id) => writeln!(out, "s {}", id)?,
+            None => {}
+        }
+
+        self.display.write_to_buf(out)?;
+
+        for (i, poly) in self.polys.iter().enumerate() {
+            write_preserved(out, PreservedPosition::Poly(i))?;
             poly.write_to_buf(out)?;
         }
 
-        for line in &self.lines {
+        for (i, line) in self.lines.iter().enumerate() {
+            write_preserved(out, PreservedPosition::Line(i))?;
             line.write_to_buf(out)?;
         }
 
+        for (i, point) in self.points.iter().enumerate() {
+            write_preserved(out, PreservedPosition::Point(i))?;
+            point.write_to_buf(out)?;
+        }
+
+        write_preserved(out, PreservedPosition::End)?;
+
         Ok(())
     }
 }
 
+/// Number of components used for the texture coordinates in `vt ...` statements.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, PartialOrd, Eq, Ord)]
+pub enum TextureDim {
+    /// 1D texture coordinates: `vt u`.
+    U,
+    /// 2D texture coordinates: `vt u v`.
+    #[default]
+    UV,
+    /// 3D texture coordinates: `vt u v w`.
+    UVW,
+}
+
+/// The kind of data referenced by an index in an element.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum IndexKind {
+    /// A vertex position declared by `v`.
+    Position,
+    /// Texture coordinates declared by `vt`.
+    Texture,
+    /// A normal declared by `vn`.
+    Normal,
+    /// A parameter space vertex declared by `vp`.
+    Parameter,
+    /// A curve on a surface declared by `curv2`.
+    Curve2,
+}
+
+impl fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexKind::Position => write!(f, "position"),
+            IndexKind::Texture => write!(f, "texture"),
+            IndexKind::Normal => write!(f, "normal"),
+            IndexKind::Parameter => write!(f, "parameter"),
+            IndexKind::Curve2 => write!(f, "curv2"),
+        }
+    }
+}
+
 /// The data model associated with each `Obj` file.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ObjData {
     /// Vertex positions.
     pub position: Vec<[f32; 3]>,
-    /// 2D texture coordinates.
+    /// Per-vertex RGB colors given after the position on `v` lines.
+    ///
+    /// This is either empty or has the same length as `position`.
+    pub color: Vec<[f32; 3]>,
+    /// Optional homogeneous `w` components of `position`.
+    ///
+    /// This is empty if no `v` line specifies a `w` component. Otherwise it has the same length
+    /// as `position` with a value of 1 for vertices that don't specify one.
+    pub position_w: Vec<f32>,
+    /// Texture coordinates.
+    ///
+    /// The `v` component is 0 for 1D texture coordinates.
     pub texture: Vec<[f32; 2]>,
+    /// The `w` components of 3D texture coordinates.
+    ///
+    /// This is empty unless `texture_dim` is `TextureDim::UVW`, in which case it has the same
+    /// length as `texture`.
+    pub texture_w: Vec<f32>,
+    /// Number of components of the texture coordinates.
+    ///
+    /// When texture coordinates of different dimensions are mixed, this is the largest one.
+    pub texture_dim: TextureDim,
     /// A set of normals.
     pub normal: Vec<[f32; 3]>,
+    /// Parameter space vertices of free-form curves and surfaces.
+    pub parameter: Vec<ParamVertex>,
+    /// Free-form curves and surfaces.
+    pub free_form: FreeForm,
     /// A collection of associated objects indicated by `o`, as well as the default object at the
     /// top level.
     pub objects: Vec<Object>,
     /// The set of all `mtllib` references to .mtl files.
     pub material_libs: Vec<Mtl>,
-}
-
-impl Default for ObjData {
-    fn default() -> Self {
-        ObjData {
-            position: Vec::new(),
-            texture: Vec::new(),
-            normal: Vec::new(),
-            objects: Vec::new(),
-            material_libs: Vec::new(),
-        }
-    }
+    /// Texture map libraries referenced by `maplib` commands.
+    pub map_libs: Vec<String>,
+    /// File containing the object used to cast shadows, given by `shadow_obj`.
+    pub shadow_obj: Option<String>,
+    /// File containing the object used for ray tracing, given by `trace_obj`.
+    pub trace_obj: Option<String>,
+    /// Files called by `call` statements that were recorded instead of being resolved.
+    pub calls: Vec<Call>,
+    /// Commands of `csh` statements, which are never executed.
+    pub shell_commands: Vec<ShellCommand>,
+    /// Comments and unrecognized lines preceding the first object or group.
+    ///
+    /// This is only set when loading with [`LoadConfig::preserve_lines`]. The writer emits these
+    /// lines first if set, and a header comment otherwise.
+    pub header: Option<Vec<String>>,
+    /// How the text of the file was decoded, which the writer uses to encode it again.
+    pub encoding: NameEncoding,
 }
 
 /// A struct used to store `Obj` data as well as its source directory used to load the referenced
@@ -445,12 +1003,17 @@ pub struct Obj {
     pub path: PathBuf,
 }
 
+/// The resolver used when none is given, which fails to open any called file.
+fn no_resolver(_: &Path, _: &str) -> io::Result<(io::Empty, PathBuf)> {
+    Err(io::Error::new(io::ErrorKind::NotFound, "no resolver for called files"))
+}
+
 /// Convert absolute 1-based vertex numbers or relative negative vertex numbers into 0-based index.
 ///
-/// If the given index is 0, then None is returned.
-fn normalize(idx: isize, len: usize) -> Option<usize> {
+/// If the given index is 0 or refers to an item before the first one, then None is returned.
+pub(crate) fn normalize(idx: isize, len: usize) -> Option<usize> {
     if idx < 0 {
-        Some((len as isize + idx) as usize)
+        len.checked_sub(idx.unsigned_abs())
     } else if idx > 0 {
         Some(idx as usize - 1)
     } else {
@@ -462,38 +1025,111 @@ impl Obj {
     /// Save the current `Obj` at the given file path as well as any associated .mtl files.
     ///
     /// If a file already exists, it will be overwritten.
-    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ObjError> {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
         self.data.save(path.as_ref())
     }
 }
 
 impl Obj {
     /// Load an `Obj` file from the given path with the default load configuration.
-    pub fn load(path: impl AsRef<Path>) -> Result<Obj, ObjError> {
+    pub fn load(path: impl AsRef<Path>) -> Result<Obj, Error> {
         Self::load_with_config(path, LoadConfig::default())
     }
 
     /// Load an `Obj` file from the given path using a custom load configuration.
-    pub fn load_with_config(path: impl AsRef<Path>, config: LoadConfig) -> Result<Obj, ObjError> {
-        Obj::load_impl(path.as_ref(), config)
+    pub fn load_with_config(path: impl AsRef<Path>, config: LoadConfig) -> Result<Obj, Error> {
+        Obj::load_impl(path.as_ref(), config, &mut |_| ())
     }
 
-    fn load_impl(path: &Path, config: LoadConfig) -> Result<Obj, ObjError> {
-        let f = File::open(path)?;
-        let data = ObjData::load_buf_with_config(&f, config)?;
+    /// Load an `Obj` file from the given path, returning it together with the problems that were
+    /// fixed up while loading.
+    pub fn load_with_warnings(path: impl AsRef<Path>, config: LoadConfig) -> Result<(Obj, Vec<Warning>), Error> {
+        let mut warnings = Vec::new();
+        let obj = Obj::load_impl(path.as_ref(), config, &mut |w| warnings.push(w))?;
+        Ok((obj, warnings))
+    }
 
-        // unwrap is safe since we've read this file before.
-        let path = path.parent().unwrap().to_owned();
+    /// Load an `Obj` file from the given path by mapping it into memory instead of reading it.
+    ///
+    /// Regular files are parsed straight from the mapped bytes, on all threads if the `rayon`
+    /// feature is enabled as well. Pipes, devices and other files that can't be mapped are read
+    /// like in [`load_with_config`].
+    ///
+    /// The file must not be modified by other processes while it is loaded.
+    ///
+    /// [`load_with_config`]: #method.load_with_config
+    #[cfg(feature = "memmap")]
+    pub fn load_mmap(path: impl AsRef<Path>, config: LoadConfig) -> Result<Obj, Error> {
+        let path = path.as_ref();
+        let f = File::open(path).map_err(|err| Error::obj(path, Operation::Load, err))?;
+
+        // Files in `/proc` and the like claim to be empty, and their contents can only be read.
+        let mmap = match f.metadata() {
+            // SAFETY: The mapping is only read while loading, which is sound as long as the file
+            // isn't truncated or modified at the same time, as documented above.
+            Ok(metadata) if metadata.is_file() && metadata.len() > 0 => unsafe { Mmap::map(&f) }.ok(),
+            _ => None,
+        };
+        match mmap {
+            #[cfg(feature = "rayon")]
+            Some(mmap) => Obj::load_from(path, ParallelReader::new(&mmap, config), &mut |_| ()),
+            #[cfg(not(feature = "rayon"))]
+            Some(mmap) => Obj::load_from(path, ObjReader::new(&mmap[..], config), &mut |_| ()),
+            None => Obj::load_from(path, ObjReader::new(&f, config), &mut |_| ()),
+        }
+    }
 
-        Ok(Obj { data, path })
+    fn load_impl(path: &Path, config: LoadConfig, warn: &mut dyn FnMut(Warning)) -> Result<Obj, Error> {
+        let f = File::open(path).map_err(|err| Error::obj(path, Operation::Load, err))?;
+        Obj::load_from(path, ObjReader::new(&f, config), warn)
+    }
+
+    /// Load the data of the file that was opened at the given path.
+    fn load_from(path: &Path, reader: impl StatementSource, warn: &mut dyn FnMut(Warning)) -> Result<Obj, Error> {
+        // unwrap is safe since we've read this file before.
+        let dir = path.parent().unwrap().to_owned();
+
+        // Files are identified by their canonical path, so that differently named calls of the same
+        // file are found to be cycles. Called files are looked up next to the file calling them.
+        let encoding = reader.config().encoding;
+        let mut resolve = |caller_dir: &Path, filename: &str| {
+            let path = caller_dir.join(encoding.path(filename));
+            let file = File::open(&path)?;
+            Ok((file, fs::canonicalize(&path)?))
+        };
+        let mut calls = vec![Include {
+            path: None,
+            identity: fs::canonicalize(path).unwrap_or_else(|_| include::normalize(path)),
+        }];
+        let data = ObjData::load_impl(reader, &mut resolve, &mut (), &mut calls, warn, None)
+            .map_err(|err| Error::obj(path, Operation::Load, err.with_path(path)))?;
+
+        Ok(Obj { data, path: dir })
     }
 
     /// Loads the .mtl files referenced in the .obj file.
     ///
-    /// If it encounters an error for an .mtl, it appends its error to the
-    /// returning Vec, and tries the rest.
-    pub fn load_mtls(&mut self) -> Result<(), MtlLibsLoadError> {
-        self.load_mtls_fn(|obj_dir, mtllib| File::open(&obj_dir.join(mtllib)).map(BufReader::new))
+    /// If it encounters an error for an .mtl, it tries the rest and returns all errors in
+    /// [`Error::MaterialLibraries`].
+    pub fn load_mtls(&mut self) -> Result<(), Error> {
+        let encoding = self.data.encoding;
+        self.load_mtls_fn(|obj_dir, mtllib| File::open(obj_dir.join(encoding.path(mtllib))).map(BufReader::new))
+    }
+
+    /// Loads the .mtl files referenced in the .obj file, returning the problems that were fixed
+    /// up while loading them and assigning their materials.
+    ///
+    /// Material libraries that fail to load are reported as in [`load_mtls`].
+    ///
+    /// [`load_mtls`]: #method.load_mtls
+    pub fn load_mtls_with_warnings(&mut self) -> Result<Vec<Warning>, Error> {
+        let mut warnings = Vec::new();
+        let encoding = self.data.encoding;
+        self.load_mtls_with_warning_fn(
+            |obj_dir, mtllib| File::open(obj_dir.join(encoding.path(mtllib))).map(BufReader::new),
+            |w| warnings.push(w),
+        )?;
+        Ok(warnings)
     }
 
     /// Loads the .mtl files referenced in the .obj file with user provided loading logic.
@@ -512,16 +1148,47 @@ impl Obj {
     ///
     /// [`load_mtls`]: #method.load_mtls
     /// [`io::BufRead`]: https://doc.rust-lang.org/std/io/trait.BufRead.html
-    pub fn load_mtls_fn<R, F>(&mut self, mut resolve: F) -> Result<(), MtlLibsLoadError>
+    pub fn load_mtls_fn<R, F>(&mut self, resolve: F) -> Result<(), Error>
     where
         R: io::BufRead,
         F: FnMut(&Path, &str) -> io::Result<R>,
+    {
+        self.load_mtls_with_warning_fn(resolve, |_| ())
+    }
+
+    /// Loads the .mtl files referenced in the .obj file with user provided loading logic, passing
+    /// the problems that were fixed up to the given function.
+    ///
+    /// See also [`load_mtls_fn`]. Besides the warnings of the material libraries themselves, a
+    /// warning is given for each group whose `usemtl` material isn't found.
+    ///
+    /// [`load_mtls_fn`]: #method.load_mtls_fn
+    pub fn load_mtls_with_warning_fn<R, F, W>(&mut self, mut resolve: F, mut warn: W) -> Result<(), Error>
+    where
+        R: io::BufRead,
+        F: FnMut(&Path, &str) -> io::Result<R>,
+        W: FnMut(Warning),
     {
         let mut errs = Vec::new();
         let mut materials = HashMap::new();
 
         for mtl_lib in &mut self.data.material_libs {
-            match mtl_lib.reload_with(&self.path, &mut resolve) {
+            let filename = mtl_lib.filename.clone();
+            let path = self.path.join(&filename);
+            let input = match resolve(&self.path, &filename) {
+                Ok(input) => input,
+                Err(err) => {
+                    errs.push(Error::mtl(path, Operation::Resolve, err));
+                    continue;
+                }
+            };
+            let loaded = mtl_lib.reload_with_warning_fn(input, |warning| {
+                warn(Warning::MaterialLibrary {
+                    filename: filename.clone(),
+                    warning: Box::new(warning),
+                })
+            });
+            match loaded {
                 Ok(mtl_lib) => {
                     for m in &mtl_lib.materials {
                         // We don't want to overwrite existing entries because of how the materials
@@ -532,9 +1199,7 @@ impl Obj {
                         materials.entry(m.name.clone()).or_insert_with(|| Arc::clone(m));
                     }
                 }
-                Err(err) => {
-                    errs.push((mtl_lib.filename.clone(), err));
-                }
+                Err(err) => errs.push(Error::mtl(&path, Operation::Load, err.with_path(&path))),
             }
         }
 
@@ -542,8 +1207,13 @@ impl Obj {
         for object in &mut self.data.objects {
             for group in &mut object.groups {
                 if let Some(ref mut mat) = group.material {
-                    if let Some(newmat) = materials.get(mat.name()) {
-                        *mat = ObjMaterial::Mtl(Arc::clone(newmat));
+                    match materials.get(mat.name()) {
+                        Some(newmat) => *mat = ObjMaterial::Mtl(Arc::clone(newmat)),
+                        None => warn(Warning::UnresolvedMaterial {
+                            object: object.name.clone(),
+                            group: group.name.clone(),
+                            material: mat.name().to_string(),
+                        }),
                     }
                 }
             }
@@ -552,7 +1222,7 @@ impl Obj {
         if errs.is_empty() {
             Ok(())
         } else {
-            Err(errs.into())
+            Err(Error::MaterialLibraries(errs))
         }
     }
 }
@@ -561,13 +1231,15 @@ impl ObjData {
     /// Save the current `ObjData` at the given file path as well as any associated .mtl files.
     ///
     /// If a file already exists, it will be overwritten.
-    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ObjError> {
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
         self.save_impl(path.as_ref())
     }
 
-    fn save_impl(&self, path: &Path) -> Result<(), ObjError> {
-        let f = File::create(path)?;
-        self.write_to_buf(&mut BufWriter::new(f))?;
+    fn save_impl(&self, path: &Path) -> Result<(), Error> {
+        File::create(path)
+            .map_err(ObjError::from)
+            .and_then(|f| self.write_to_buf(&mut BufWriter::new(f)))
+            .map_err(|err| Error::obj(path, Operation::Save, err))?;
 
         // unwrap is safe because we created the file above.
         let path = path.parent().unwrap();
@@ -575,9 +1247,9 @@ impl ObjData {
     }
 
     /// Save all material libraries referenced in this `Obj` to the given base directory.
-    pub fn save_mtls(&self, base_dir: impl AsRef<Path>) -> Result<(), ObjError> {
+    pub fn save_mtls(&self, base_dir: impl AsRef<Path>) -> Result<(), Error> {
         self.save_mtls_with_fn(base_dir.as_ref(), |base_dir, mtllib| {
-            File::create(base_dir.join(mtllib))
+            File::create(base_dir.join(self.encoding.path(mtllib)))
         })
     }
 
@@ -586,289 +1258,608 @@ impl ObjData {
         &self,
         base_dir: &Path,
         mut resolve: impl FnMut(&Path, &str) -> io::Result<W>,
-    ) -> Result<(), ObjError> {
+    ) -> Result<(), Error> {
         for mtl in &self.material_libs {
-            mtl.write_to_buf(&mut resolve(base_dir, &mtl.filename)?)?;
+            let path = base_dir.join(&mtl.filename);
+            let mut out = resolve(base_dir, &mtl.filename).map_err(|err| Error::mtl(&path, Operation::Resolve, err))?;
+            mtl.write_to_buf(&mut out)
+                .map_err(|err| Error::mtl(path, Operation::Save, err))?;
         }
         Ok(())
     }
 
     /// Serialize this `Obj` into the given writer.
+    ///
+    /// The text is encoded with [`ObjData::encoding`], so names that were decoded from another
+    /// encoding are written with their original bytes.
     pub fn write_to_buf(&self, out: &mut impl Write) -> Result<(), ObjError> {
-        writeln!(
-            out,
-            "# Generated by the obj Rust library (https://crates.io/crates/obj)."
-        )?;
+        match self.encoding {
+            NameEncoding::Utf8Lossy => self.write_text(out),
+            encoding => self.write_text(&mut EncodingWriter::new(out, encoding)),
+        }
+    }
+
+    /// Write this `Obj` as text, leaving its encoding to `out`.
+    fn write_text(&self, out: &mut impl Write) -> Result<(), ObjError> {
+        match self.header {
+            Some(ref header) => {
+                for line in header {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            None => writeln!(
+                out,
+                "# Generated by the obj Rust library (https://crates.io/crates/obj)."
+            )?,
+        }
 
-        for pos in &self.position {
-            writeln!(out, "v {} {} {}", pos[0], pos[1], pos[2])?;
+        for (i, pos) in self.position.iter().enumerate() {
+            write!(out, "v {} {} {}", pos[0], pos[1], pos[2])?;
+            if let Some(w) = self.position_w.get(i) {
+                write!(out, " {}", w)?;
+            }
+            if let Some(col) = self.color.get(i) {
+                write!(out, " {} {} {}", col[0], col[1], col[2])?;
+            }
+            writeln!(out)?;
         }
-        for uv in &self.texture {
-            writeln!(out, "vt {} {}", uv[0], uv[1])?;
+        for (i, uv) in self.texture.iter().enumerate() {
+            match self.texture_dim {
+                TextureDim::U => writeln!(out, "vt {}", uv[0])?,
+                TextureDim::UV => writeln!(out, "vt {} {}", uv[0], uv[1])?,
+                TextureDim::UVW => writeln!(
+                    out,
+                    "vt {} {} {}",
+                    uv[0],
+                    uv[1],
+                    self.texture_w.get(i).copied().unwrap_or(0.0)
+                )?,
+            }
         }
         for nml in &self.normal {
             writeln!(out, "vn {} {} {}", nml[0], nml[1], nml[2])?;
         }
+        for vp in &self.parameter {
+            writeln!(out, "vp {}", vp)?;
+        }
+        self.free_form.write_to_buf(out)?;
         for object in &self.objects {
             object.write_to_buf(out)?;
         }
         for mtl_lib in &self.material_libs {
             writeln!(out, "mtllib {}", mtl_lib.filename)?;
         }
+        for map_lib in &self.map_libs {
+            writeln!(out, "maplib {}", map_lib)?;
+        }
+        if let Some(ref shadow_obj) = self.shadow_obj {
+            writeln!(out, "shadow_obj {}", shadow_obj)?;
+        }
+        if let Some(ref trace_obj) = self.trace_obj {
+            writeln!(out, "trace_obj {}", trace_obj)?;
+        }
+        for call in &self.calls {
+            call.write_to_buf(out)?;
+        }
+        for command in &self.shell_commands {
+            writeln!(out, "{}", command)?;
+        }
 
         Ok(())
     }
+
+    /// Iterate over the polygons of all groups with the given name, including groups that list
+    /// the name among several ones.
+    pub fn faces_in_group<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SimplePolygon> + 'a {
+        self.objects
+            .iter()
+            .flat_map(|object| &object.groups)
+            .filter(move |group| group.names().any(|n| n == name))
+            .flat_map(|group| &group.polys)
+    }
 }
 
 impl ObjData {
-    fn parse_two(line_number: usize, n0: Option<&str>, n1: Option<&str>) -> Result<[f32; 2], ObjError> {
-        let (n0, n1) = match (n0, n1) {
-            (Some(n0), Some(n1)) => (n0, n1),
-            _ => {
-                return Err(ObjError::ArgumentListFailure {
-                    line_number,
-                    list: format!("{:?} {:?}", n0, n1),
-                });
-            }
-        };
-        let normal = match (FromStr::from_str(n0), FromStr::from_str(n1)) {
-            (Ok(n0), Ok(n1)) => [n0, n1],
-            _ => {
-                return Err(ObjError::ArgumentListFailure {
-                    line_number,
-                    list: format!("{:?} {:?}", n0, n1),
-                });
+    /// Keep a comment or unrecognized line at the current position in the object stream.
+    ///
+    /// Within a group, the line waits in `pending` for the next statement of the group, before
+    /// which it is written.
+    fn preserve_line(&mut self, object: &mut Object, group: Option<&Group>, pending: &mut Vec<String>, text: &str) {
+        let text = text.to_string();
+        match group {
+            Some(_) => pending.push(text),
+            None if self.objects.is_empty() && object.groups.is_empty() && object.name == DEFAULT_OBJECT => {
+                self.header.get_or_insert_with(Vec::new).push(text)
             }
-        };
-        Ok(normal)
+            None => object.preserved.push(text),
+        }
     }
 
-    fn parse_three(
-        line_number: usize,
-        n0: Option<&str>,
-        n1: Option<&str>,
-        n2: Option<&str>,
-    ) -> Result<[f32; 3], ObjError> {
-        let (n0, n1, n2) = match (n0, n1, n2) {
-            (Some(n0), Some(n1), Some(n2)) => (n0, n1, n2),
-            _ => {
-                return Err(ObjError::ArgumentListFailure {
-                    line_number,
-                    list: format!("{:?} {:?} {:?}", n0, n1, n2),
-                });
-            }
-        };
-        let normal = match (FromStr::from_str(n0), FromStr::from_str(n1), FromStr::from_str(n2)) {
-            (Ok(n0), Ok(n1), Ok(n2)) => [n0, n1, n2],
-            _ => {
-                return Err(ObjError::ArgumentListFailure {
-                    line_number,
-                    list: format!("{:?} {:?} {:?}", n0, n1, n2),
-                });
-            }
-        };
-        Ok(normal)
+    /// Get the body of the free-form element that is open for body statements, which the reader
+    /// only yields within an element.
+    fn free_form_body(&mut self, element: Option<FreeFormElement>) -> &mut FreeFormBody {
+        self.free_form
+            .body_mut(element.expect("body statements are only read within a free-form element"))
     }
 
-    fn parse_group(&self, line_number: usize, group: &str) -> Result<IndexTuple, ObjError> {
-        let mut group_split = group.split('/');
-        let p: Option<isize> = group_split.next().and_then(|idx| FromStr::from_str(idx).ok());
-        let t: Option<isize> = group_split.next().and_then(|idx| {
-            if !idx.is_empty() {
-                FromStr::from_str(idx).ok()
-            } else {
-                None
-            }
-        });
-        let n: Option<isize> = group_split.next().and_then(|idx| FromStr::from_str(idx).ok());
-
-        match (p, t, n) {
-            (Some(p), t, n) => Ok(IndexTuple(
-                normalize(p, self.position.len()).ok_or(ObjError::ZeroVertexNumber { line_number })?,
-                // Zero indices are silently ignored for tangent and normal indices.
-                t.map(|t| normalize(t, self.texture.len())).flatten(),
-                n.map(|n| normalize(n, self.normal.len())).flatten(),
-            )),
-            _ => Err(ObjError::MalformedFaceGroup {
-                line_number,
-                group: String::from(group),
-            }),
-        }
+    pub fn load_buf<R: Read>(input: R) -> Result<Self, ObjError> {
+        Self::load_buf_with_config(input, LoadConfig::default())
     }
 
-    fn parse_face<'b, I>(&self, line_number: usize, groups: &mut I) -> Result<SimplePolygon, ObjError>
+    pub fn load_buf_with_config<R: Read>(input: R, config: LoadConfig) -> Result<Self, ObjError> {
+        Self::load_impl(
+            ObjReader::new(input, config),
+            &mut no_resolver,
+            &mut (),
+            &mut Vec::new(),
+            &mut |_| (),
+            None,
+        )
+    }
+
+    /// Load an `.obj` file, skipping statements that fail to parse instead of stopping at the
+    /// first error.
+    ///
+    /// Returns the data loaded from the remaining statements together with all errors. Errors
+    /// within files named by `call` statements are reported as a single error of the statement.
+    pub fn load_buf_recovering<R: Read>(input: R, config: LoadConfig) -> (Self, Vec<ObjError>) {
+        let mut errors = Vec::new();
+        let dat = Self::load_impl(
+            ObjReader::new(input, config),
+            &mut no_resolver,
+            &mut (),
+            &mut Vec::new(),
+            &mut |_| (),
+            Some(&mut errors),
+        )
+        .expect("errors are collected while recovering");
+        (dat, errors)
+    }
+
+    /// Load an `.obj` file, returning the loaded data together with the problems that were fixed
+    /// up while loading.
+    pub fn load_buf_with_warnings<R: Read>(input: R, config: LoadConfig) -> Result<(Self, Vec<Warning>), ObjError> {
+        let mut warnings = Vec::new();
+        let dat = Self::load_buf_with_warning_fn(input, config, |w| warnings.push(w))?;
+        Ok((dat, warnings))
+    }
+
+    /// Load an `.obj` file, passing the problems that were fixed up while loading to the given
+    /// function as they are found.
+    pub fn load_buf_with_warning_fn<R, W>(input: R, config: LoadConfig, mut warn: W) -> Result<Self, ObjError>
     where
-        I: Iterator<Item = &'b str>,
+        R: Read,
+        W: FnMut(Warning),
     {
-        let mut ret = Vec::with_capacity(4);
-        for g in groups {
-            let ituple = self.parse_group(line_number, g)?;
-            ret.push(ituple);
-        }
-        Ok(SimplePolygon(ret))
+        Self::load_impl(
+            ObjReader::new(input, config),
+            &mut no_resolver,
+            &mut (),
+            &mut Vec::new(),
+            &mut warn,
+            None,
+        )
     }
 
-    fn parse_line<'b, I>(&self, line_number: usize, groups: &mut I) -> Result<Line, ObjError>
+    /// Load an `.obj` file passing all unrecognized statements to the given handler.
+    pub fn load_buf_with_handler<R, H>(input: R, config: LoadConfig, handler: &mut H) -> Result<Self, ObjError>
     where
-        I: Iterator<Item = &'b str>,
+        R: Read,
+        H: StatementHandler,
     {
-        let mut ret = Vec::with_capacity(2);
-        for g in groups {
-            let ituple = self.parse_group(line_number, g)?;
-            if ituple.2.is_some() {
-                return Err(ObjError::LineHasNormalIndex { line_number });
-            }
-            ret.push(ituple.into());
-        }
-        Ok(Line(ret))
+        Self::load_impl(
+            ObjReader::new(input, config),
+            &mut no_resolver,
+            handler,
+            &mut Vec::new(),
+            &mut |_| (),
+            None,
+        )
     }
 
-    pub fn load_buf<R: Read>(input: R) -> Result<Self, ObjError> {
-        Self::load_buf_with_config(input, LoadConfig::default())
+    /// Load an `.obj` file held in memory, parsing it on the threads of the global `rayon` pool.
+    ///
+    /// The loaded data is the same as that of [`load_buf_with_config`], which is faster for
+    /// small files.
+    ///
+    /// [`load_buf_with_config`]: #method.load_buf_with_config
+    #[cfg(feature = "rayon")]
+    pub fn load_buf_parallel(input: &[u8], config: LoadConfig) -> Result<Self, ObjError> {
+        Self::load_impl(
+            ParallelReader::new(input, config),
+            &mut no_resolver,
+            &mut (),
+            &mut Vec::new(),
+            &mut |_| (),
+            None,
+        )
     }
 
-    pub fn load_buf_with_config<R: Read>(input: R, config: LoadConfig) -> Result<Self, ObjError> {
-        let input = BufReader::new(input);
-        let mut dat = ObjData::default();
+    /// Load an `.obj` file using the given function to open the files named by `call` statements.
+    ///
+    /// The function is only used if `config.include` is [`IncludePolicy::Resolve`]. It is given the
+    /// file name as listed in the `call` statement. Nested calls name files relative to the file
+    /// calling them, so their names are joined to the directory of that file first. File names
+    /// that only differ by `.` and `..` components are taken to name the same file.
+    pub fn load_buf_with_resolver<R, I, F>(input: R, config: LoadConfig, mut resolve: F) -> Result<Self, ObjError>
+    where
+        R: Read,
+        I: Read,
+        F: FnMut(&str) -> io::Result<I>,
+    {
+        Self::load_impl(
+            ObjReader::new(input, config),
+            &mut |caller_dir: &Path, filename: &str| {
+                let filename = caller_dir.join(filename);
+                let filename = filename.to_string_lossy();
+                Ok((resolve(&filename)?, include::normalize(Path::new(&*filename))))
+            },
+            &mut (),
+            &mut Vec::new(),
+            &mut |_| (),
+            None,
+        )
+    }
+
+    fn load_impl<S, I, F, H>(
+        mut reader: S,
+        resolve: &mut F,
+        handler: &mut H,
+        calls: &mut Vec<Include>,
+        warn: &mut dyn FnMut(Warning),
+        mut errors: Option<&mut Vec<ObjError>>,
+    ) -> Result<Self, ObjError>
+    where
+        S: StatementSource,
+        I: Read,
+        F: FnMut(&Path, &str) -> io::Result<(I, PathBuf)>,
+        H: StatementHandler,
+    {
+        let config = reader.config();
+        let path = calls.last().and_then(|call| call.path.clone());
+        let mut dat = ObjData {
+            header: Some(Vec::new()).filter(|_| config.preserve_lines),
+            encoding: config.encoding,
+            ..ObjData::default()
+        };
         let mut object = Object::new(DEFAULT_OBJECT.to_string());
         let mut group: Option<Group> = None;
-
-        for (idx, line) in input.lines().enumerate() {
-            let (line, mut words) = match line {
-                Ok(ref line) => (line.clone(), line.split_whitespace().filter(|s| !s.is_empty())),
-                Err(err) => {
-                    return Err(ObjError::Io(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("failed to readline {}", err),
-                    )));
-                }
-            };
-            let first = words.next();
-
-            match first {
-                Some("v") => {
-                    let (v0, v1, v2) = (words.next(), words.next(), words.next());
-                    dat.position.push(Self::parse_three(idx, v0, v1, v2)?);
-                }
-                Some("vt") => {
-                    let (t0, t1) = (words.next(), words.next());
-                    dat.texture.push(Self::parse_two(idx, t0, t1)?);
-                }
-                Some("vn") => {
-                    let (n0, n1, n2) = (words.next(), words.next(), words.next());
-                    dat.normal.push(Self::parse_three(idx, n0, n1, n2)?);
-                }
-                Some("f") => {
-                    let poly = dat.parse_face(idx, &mut words)?;
-                    group = Some(match group {
-                        None => {
-                            let mut g = Group::new(DEFAULT_GROUP.to_string());
-                            g.polys.push(poly);
-                            g
+        let mut free_form_attributes = FreeFormAttributes::default();
+        let mut free_form_element = None;
+        let mut merging_group = None;
+        // Preserved lines of the current group, which wait for the statement that follows them.
+        let mut pending = Vec::new();
+
+        while let Some((idx, statement)) = reader.next_statement() {
+            // Unrecognized statements are preserved once the reader is done with the statement.
+            let mut preserve = false;
+            let mut spliced = false;
+
+            // Each statement is handled by a closure so that errors can be collected in recovering
+            // mode, skipping the rest of the offending statement.
+            let result = statement.and_then(|statement| {
+                // Pending lines are placed before the statements that end the current group or
+                // change its state.
+                if let Some(g) = group.as_mut() {
+                    match statement {
+                        Statement::Object(_) | Statement::Group(_) | Statement::Call { .. } => {
+                            g.keep_lines(&mut pending, PreservedPosition::End)
                         }
-                        Some(mut g) => {
-                            g.polys.push(poly);
-                            g
+                        Statement::UseMtl(_)
+                        | Statement::Smoothing(_)
+                        | Statement::Bevel(_)
+                        | Statement::ColorInterpolation(_)
+                        | Statement::DissolveInterpolation(_)
+                        | Statement::Lod(_)
+                        | Statement::UseMap(_) => {
+                            let position = if g.has_geometry() {
+                                PreservedPosition::End
+                            } else {
+                                PreservedPosition::Start
+                            };
+                            g.keep_lines(&mut pending, position);
                         }
-                    });
+                        _ => {}
+                    }
                 }
-                Some("l") => {
-                    let line = dat.parse_line(idx, &mut words)?;
-                    group = Some(match group {
-                        None => {
-                            let mut g = Group::new(DEFAULT_GROUP.to_string());
-                            g.lines.push(line);
-                            g
+                match statement {
+                    Statement::Vertex { position, w, color } => {
+                        // Weights default to 1, so they are filled in for vertices that don't
+                        // specify them as soon as any vertex does.
+                        if w.is_some() || !dat.position_w.is_empty() {
+                            dat.position_w.resize(dat.position.len(), 1.0);
+                            dat.position_w.push(w.unwrap_or(1.0));
                         }
-                        Some(mut g) => {
-                            g.lines.push(line);
-                            g
+
+                        dat.position.push(position);
+                        dat.color.extend(color);
+                    }
+                    Statement::TexCoord { uvw, dim } => {
+                        // Missing `v` and `w` components default to 0, which lets files mixing
+                        // different dimensions be stored with the largest one.
+                        if dat.texture.is_empty() || dim > dat.texture_dim {
+                            dat.texture_dim = dim;
                         }
-                    });
-                }
-                Some("o") => {
-                    group = match group {
-                        Some(val) => {
-                            object.groups.push(val);
-                            dat.objects.push(object);
-                            None
+                        if dim == TextureDim::UVW || !dat.texture_w.is_empty() {
+                            dat.texture_w.resize(dat.texture.len(), 0.0);
+                            dat.texture_w.push(uvw[2]);
                         }
-                        None => None,
-                    };
-                    object = if line.len() > 2 {
-                        let name = line[1..].trim();
-                        Object::new(name.to_string())
-                    } else {
-                        Object::new(DEFAULT_OBJECT.to_string())
-                    };
-                }
-                Some("g") => {
-                    let current_material = if group.is_some() {
-                        let g = group.as_ref().unwrap();
-                        g.material.clone()
-                    } else {
-                        None
-                    };
-
-                    object.groups.extend(group.take());
-
-                    if line.len() > 2 {
-                        let name = line[2..].trim();
-                        let mut g = Group::new(name.to_string());
-                        g.material = current_material;
+
+                        dat.texture.push([uvw[0], uvw[1]]);
+                    }
+                    Statement::Normal(normal) => dat.normal.push(normal),
+                    Statement::ParamVertex(vp) => dat.parameter.push(vp),
+                    Statement::Face(indices) => {
+                        let g = Group::for_element(&mut group, &mut object, merging_group);
+                        g.keep_lines(&mut pending, PreservedPosition::Poly(g.polys.len()));
+                        g.polys.push(SimplePolygon(indices.to_vec()));
+                    }
+                    Statement::Line(indices) => {
+                        let g = Group::for_element(&mut group, &mut object, merging_group);
+                        g.keep_lines(&mut pending, PreservedPosition::Line(g.lines.len()));
+                        g.lines.push(Line(indices.to_vec()));
+                    }
+                    Statement::Point(indices) => {
+                        let g = Group::for_element(&mut group, &mut object, merging_group);
+                        g.keep_lines(&mut pending, PreservedPosition::Point(g.points.len()));
+                        g.points.push(Point(indices.to_vec()));
+                    }
+                    Statement::Object(name) => {
+                        let mut finished = std::mem::replace(&mut object, Object::new(name.to_string()));
+                        finished.groups.extend(group.take());
+                        // An object without groups is only kept for its preserved lines.
+                        if !finished.groups.is_empty() || !finished.preserved.is_empty() {
+                            dat.objects.push(finished);
+                        }
+                    }
+                    Statement::Group(names) => {
+                        let current_material = group.as_ref().and_then(|g| g.material.clone());
+                        let current_smoothing_group = group.as_ref().and_then(|g| g.smoothing_group);
+                        let current_display = group.as_ref().map(|g| g.display.clone()).unwrap_or_default();
+
+                        object.groups.extend(group.take());
+
+                        let mut names: Vec<String> = if config.single_group_name {
+                            Some(names)
+                                .filter(|name| !name.is_empty())
+                                .map(String::from)
+                                .into_iter()
+                                .collect()
+                        } else {
+                            names.split_whitespace().map(String::from).collect()
+                        };
+                        if !names.is_empty() {
+                            let mut g = Group::new(names.remove(0));
+                            g.additional_names = names;
+                            g.material = current_material;
+                            g.smoothing_group = current_smoothing_group;
+                            g.merging_group = merging_group;
+                            g.display = current_display;
+                            group = Some(g);
+                        }
+                    }
+                    Statement::MtlLib(name) => dat.material_libs.push(Mtl {
+                        encoding: config.encoding,
+                        ..Mtl::new(name.to_string())
+                    }),
+                    Statement::UseMtl(name) => {
+                        // we found a new material that was applied to an existing
+                        // non-empty object. It is treated as a new group.
+                        let mut g = Group::split(group.take(), &mut object);
+                        g.material = name.map(|w| ObjMaterial::Ref(w.to_string()));
                         group = Some(g);
                     }
-                }
-                Some("mtllib") => {
-                    // Obj strictly does not allow spaces in filenames.
-                    // "mtllib Some File.mtl" is forbidden.
-                    // However, everyone does it anyway and if we want to ingest blender-outputted files, we need to support it.
-                    // This works by walking word by word and combining them with a space in between. This may not be a totally
-                    // accurate way to do it, but until the parser can be re-worked, this is good-enough, better-than-before solution.
-                    let first_word = words
-                        .next()
-                        .ok_or(ObjError::MissingMTLName { line_number: idx })?
-                        .to_string();
-                    let name = words.fold(first_word, |mut existing, next| {
-                        existing.push(' ');
-                        existing.push_str(next);
-                        existing
-                    });
-                    dat.material_libs.push(Mtl::new(name));
-                }
-                Some("usemtl") => {
-                    let mut g = group.unwrap_or_else(|| Group::new(DEFAULT_GROUP.to_string()));
-                    let group_has_geometry = !g.polys.is_empty() || !g.lines.is_empty();
-
-                    // we found a new material that was applied to an existing
-                    // non-empty object. It is treated as a new group.
-                    if group_has_geometry {
-                        object.groups.push(g.clone());
-                        g.index += 1;
-                        g.polys.clear();
-                        g.lines.clear();
+                    Statement::Smoothing(smoothing_group) => {
+                        // Like materials, smoothing groups apply to whole groups.
+                        if group.as_ref().and_then(|g| g.smoothing_group) != Some(smoothing_group) {
+                            let mut g = Group::split(group.take(), &mut object);
+                            g.smoothing_group = Some(smoothing_group);
+                            group = Some(g);
+                        }
                     }
-                    g.material = words.next().map(|w| ObjMaterial::Ref(w.to_string()));
-                    group = Some(g);
-                }
-                Some("s") => (),
-                Some(other) => {
-                    if config.strict && !other.starts_with('#') {
-                        return Err(ObjError::UnexpectedCommand {
+                    Statement::Bevel(on) => Group::set_display(&mut group, &mut object, |d| d.bevel = Some(on)),
+                    Statement::ColorInterpolation(on) => {
+                        Group::set_display(&mut group, &mut object, |d| d.color_interpolation = Some(on))
+                    }
+                    Statement::DissolveInterpolation(on) => {
+                        Group::set_display(&mut group, &mut object, |d| d.dissolve_interpolation = Some(on))
+                    }
+                    Statement::Lod(level) => Group::set_display(&mut group, &mut object, |d| d.lod = Some(level)),
+                    Statement::UseMap(name) => Group::set_display(&mut group, &mut object, |d| {
+                        d.texture_map = Some(name.map(String::from))
+                    }),
+                    Statement::MapLib(names) => dat.map_libs.extend(names.split_whitespace().map(String::from)),
+                    Statement::ShadowObj(name) => dat.shadow_obj = Some(name.to_string()),
+                    Statement::TraceObj(name) => dat.trace_obj = Some(name.to_string()),
+                    Statement::MergingGroup(mg) => merging_group = mg,
+                    Statement::CurveType { rational, cstype } => {
+                        free_form_attributes.rational = rational;
+                        free_form_attributes.cstype = Some(cstype);
+                    }
+                    Statement::Degree(u, v) => free_form_attributes.degree = Some((u, v)),
+                    Statement::BasisU(matrix) => free_form_attributes.basis_u = Some(matrix.to_vec()),
+                    Statement::BasisV(matrix) => free_form_attributes.basis_v = Some(matrix.to_vec()),
+                    Statement::Step(u, v) => free_form_attributes.step = Some((u, v)),
+                    Statement::CurveTechnique(technique) => free_form_attributes.curve_technique = Some(technique),
+                    Statement::SurfaceTechnique(technique) => free_form_attributes.surface_technique = Some(technique),
+                    Statement::Curve { range, vertices } => {
+                        free_form_element = Some(FreeFormElement::Curve(dat.free_form.curves.len()));
+                        dat.free_form.curves.push(Curve {
+                            attributes: free_form_attributes.clone(),
+                            range,
+                            vertices: vertices.to_vec(),
+                            body: FreeFormBody::default(),
+                        });
+                    }
+                    Statement::Curve2(vertices) => {
+                        free_form_element = Some(FreeFormElement::Curve2(dat.free_form.curves2.len()));
+                        dat.free_form.curves2.push(Curve2 {
+                            attributes: free_form_attributes.clone(),
+                            vertices: vertices.to_vec(),
+                            body: FreeFormBody::default(),
+                        });
+                    }
+                    Statement::Surface {
+                        range_u,
+                        range_v,
+                        vertices,
+                    } => {
+                        free_form_element = Some(FreeFormElement::Surface(dat.free_form.surfaces.len()));
+                        dat.free_form.surfaces.push(Surface {
+                            attributes: free_form_attributes.clone(),
+                            range_u,
+                            range_v,
+                            vertices: vertices.to_vec(),
+                            merging_group,
+                            body: FreeFormBody::default(),
+                        });
+                    }
+                    Statement::ParmU(values) => dat.free_form_body(free_form_element).parm_u.extend_from_slice(values),
+                    Statement::ParmV(values) => dat.free_form_body(free_form_element).parm_v.extend_from_slice(values),
+                    Statement::Trim(refs) => dat.free_form_body(free_form_element).trims.push(refs.to_vec()),
+                    Statement::Hole(refs) => dat.free_form_body(free_form_element).holes.push(refs.to_vec()),
+                    Statement::SpecialCurve(refs) => {
+                        dat.free_form_body(free_form_element).special_curves.push(refs.to_vec())
+                    }
+                    Statement::SpecialPoints(points) => dat
+                        .free_form_body(free_form_element)
+                        .special_points
+                        .extend_from_slice(points),
+                    Statement::End => free_form_element = None,
+                    Statement::Call { filename, args } if config.include == IncludePolicy::Resolve => {
+                        if calls.iter().filter(|call| call.path.is_some()).count() >= MAX_INCLUDE_DEPTH {
+                            return Err(ObjError::IncludeTooDeep {
+                                line_number: idx,
+                                filename: filename.to_string(),
+                            });
+                        }
+                        let failure = |error| ObjError::IncludeFailure {
                             line_number: idx,
-                            command: other.to_string(),
+                            filename: filename.to_string(),
+                            error: Box::new(error),
+                        };
+
+                        // The directory of the calling file, which is empty for the top-level buffer.
+                        let caller_dir = calls
+                            .last()
+                            .and_then(|call| call.identity.parent())
+                            .unwrap_or(Path::new(""));
+                        let (input, identity) =
+                            resolve(caller_dir, filename).map_err(|err| failure(ObjError::Io(err)))?;
+                        if calls.iter().any(|call| call.identity == identity) {
+                            return Err(ObjError::IncludeCycle {
+                                line_number: idx,
+                                filename: filename.to_string(),
+                            });
+                        }
+                        let args = args.split_whitespace().map(String::from).collect();
+                        calls.push(Include {
+                            path: Some(PathBuf::from(filename)),
+                            identity,
                         });
+                        let mut warn_called = |warning| {
+                            warn(Warning::Called {
+                                line_number: idx,
+                                filename: filename.to_string(),
+                                warning: Box::new(warning),
+                            })
+                        };
+                        let reader = ObjReader::with_args(input, config, args);
+                        let called = Self::load_impl(reader, resolve, handler, calls, &mut warn_called, None);
+                        calls.pop();
+                        let mut called = called.map_err(failure)?;
+
+                        // Comments at the top of the called file are kept at the position of the call.
+                        for text in called.header.take().into_iter().flatten() {
+                            dat.preserve_line(&mut object, group.as_ref(), &mut pending, &text);
+                        }
+                        if let Some(g) = group.as_mut() {
+                            g.keep_lines(&mut pending, PreservedPosition::End);
+                        }
+
+                        // The called file continues the current object and group unless it starts new ones.
+                        object.groups.extend(group.take());
+                        spliced = true;
+                        for (i, called_object) in dat.splice(called, idx)?.into_iter().enumerate() {
+                            if i == 0 && called_object.name == DEFAULT_OBJECT {
+                                object.groups.extend(called_object.groups);
+                            } else {
+                                let finished = std::mem::replace(&mut object, called_object);
+                                if !finished.groups.is_empty() {
+                                    dat.objects.push(finished);
+                                }
+                            }
+                        }
+                        group = object.groups.pop();
+                    }
+                    Statement::Call { filename, args } => dat.calls.push(Call {
+                        filename: filename.to_string(),
+                        args: args.split_whitespace().map(String::from).collect(),
+                    }),
+                    Statement::ShellCommand { command, ignore_errors } => dat.shell_commands.push(ShellCommand {
+                        command: command.to_string(),
+                        ignore_errors,
+                    }),
+                    Statement::Comment(text) if config.preserve_lines => {
+                        dat.preserve_line(&mut object, group.as_ref(), &mut pending, text)
                     }
+                    Statement::Comment(_) => (),
+                    Statement::Unknown { keyword, args } => {
+                        let args: Vec<&str> = args.split_whitespace().collect();
+                        if !handler.handle(idx, keyword, &args, &mut object, group.as_mut())? {
+                            if config.strict {
+                                return Err(ObjError::UnexpectedCommand {
+                                    line_number: idx,
+                                    command: keyword.to_string(),
+                                });
+                            }
+                            warn(Warning::UnknownCommand {
+                                line_number: idx,
+                                command: keyword.to_string(),
+                            });
+                            preserve = config.preserve_lines;
+                        }
+                    }
+                }
+                Ok(())
+            });
+
+            if spliced {
+                reader.declare(&dat);
+            }
+            for warning in reader.warnings() {
+                warn(warning);
+            }
+            if preserve {
+                dat.preserve_line(&mut object, group.as_ref(), &mut pending, reader.line());
+            }
+            if let Err(err) = result {
+                // Errors reading the input aren't specific to a statement.
+                let err = match err {
+                    ObjError::Io(_) => err,
+                    err => err.located(path.as_deref(), idx, reader.line()),
+                };
+                match errors {
+                    Some(ref mut errors) => errors.push(err),
+                    None => return Err(err),
                 }
-                None => (),
             }
         }
 
-        if let Some(g) = group {
+        if let Some(mut g) = group {
+            g.keep_lines(&mut pending, PreservedPosition::End);
             object.groups.push(g);
         }
 
         dat.objects.push(object);
+
+        // Forward references are checked against the data declared in the whole file.
+        for err in reader.forward_reference_errors() {
+            match errors {
+                Some(ref mut errors) => errors.push(err),
+                None => return Err(err),
+            }
+        }
         Ok(dat)
     }
 }
@@ -883,8 +1874,8 @@ mod tests {
         let test = b"v 0 1 2\nv 3 4 5\nf 0 1 2";
         let mut reader = BufReader::new(&test[..]);
         assert!(matches!(
-            ObjData::load_buf(&mut reader),
-            Err(ObjError::ZeroVertexNumber { line_number: 2 })
+            ObjData::load_buf(&mut reader).unwrap_err().kind(),
+            ObjError::ZeroVertexNumber { line_number: 3 }
         ));
     }
 
@@ -0,0 +1,348 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Parsing of in-memory `.obj` files on multiple threads.
+//!
+//! The input is split into chunks of whole statements, in which vertex data and faces are parsed
+//! concurrently. Everything that depends on the statements before it, like relative indices and the
+//! current object, group and material, is left to a sequential pass over the parsed chunks, which
+//! feeds the same loader as the serial reader.
+
+use std::{mem, ops::Range, vec};
+
+use rayon::prelude::*;
+
+use crate::include::IncludePolicy;
+use crate::lex::{parse_index_tuple, Number, Tokens};
+use crate::lines::LogicalLines;
+use crate::obj::{LoadConfig, ObjData, ObjError, TextureDim};
+use crate::reader::{parse, Buffers, Declared, Statement, StatementSource};
+use crate::warning::Warning;
+
+/// The size of the chunks parsed by a single task.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// The number of chunks parsed at once per thread, which bounds the memory held by parsed chunks.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// A statement as far as it can be parsed without knowing the statements before it.
+enum Parsed {
+    Vertex {
+        position: [f32; 3],
+        w: Option<f32>,
+        color: Option<[f32; 3]>,
+    },
+    TexCoord {
+        uvw: [f32; 3],
+        dim: TextureDim,
+    },
+    Normal([f32; 3]),
+    /// A face with the given range of the index tuples of its chunk.
+    Face(Range<usize>),
+    /// Any other statement, including the ones above if they failed to parse, with its text.
+    Other(String),
+}
+
+struct Item {
+    /// The 1-based number of the line within its chunk.
+    line_number: usize,
+    /// The bytes of the statement within its chunk.
+    span: Range<usize>,
+    parsed: Parsed,
+}
+
+/// A range of whole statements of the input.
+struct Chunk<'a> {
+    bytes: &'a [u8],
+    /// The number of physical lines of the chunk.
+    lines: usize,
+    items: vec::IntoIter<Item>,
+    /// Index tuples with their raw `v/vt/vn` references, of which the position is known.
+    tuples: Vec<(isize, Option<isize>, Option<isize>)>,
+}
+
+impl<'a> Chunk<'a> {
+    fn empty() -> Self {
+        Chunk {
+            bytes: &[],
+            lines: 0,
+            items: Vec::new().into_iter(),
+            tuples: Vec::new(),
+        }
+    }
+
+    fn parse(bytes: &'a [u8], first: bool, config: &LoadConfig) -> Self {
+        let mut lines = if first {
+            LogicalLines::new(bytes, config.encoding)
+        } else {
+            LogicalLines::continuing(bytes, config.encoding)
+        };
+        let mut items = Vec::new();
+        let mut tuples = Vec::new();
+        let mut line = String::new();
+        loop {
+            let start = bytes.len() - lines.rest().len();
+            let line_number = match lines.next_into(&mut line) {
+                // Reading a slice never fails.
+                Some((line_number, _)) => line_number,
+                None => break,
+            };
+            let end = bytes.len() - lines.rest().len();
+
+            let mut words = Tokens::new(&line);
+            let parsed = match words.next() {
+                None => continue,
+                Some("call") | Some("csh") if config.include == IncludePolicy::Ignore => continue,
+                Some("v") => parse_vertex(words),
+                Some("vt") => parse_tex_coord(words),
+                Some("vn") => parse_normal(words),
+                Some("f") => parse_face(words, &mut tuples),
+                Some(_) => None,
+            };
+            items.push(Item {
+                line_number,
+                span: start..end,
+                parsed: parsed.unwrap_or_else(|| Parsed::Other(mem::take(&mut line))),
+            });
+        }
+        Chunk {
+            bytes,
+            lines: lines.line_number(),
+            items: items.into_iter(),
+            tuples,
+        }
+    }
+}
+
+/// Parse all arguments before a trailing comment into `out`, returning how many there are.
+fn parse_numbers<T: Number, const N: usize>(words: Tokens<'_>, out: &mut [T; N]) -> Option<usize> {
+    let mut count = 0;
+    for word in words.before_comment() {
+        *out.get_mut(count)? = T::parse(word)?;
+        count += 1;
+    }
+    Some(count)
+}
+
+fn parse_vertex(words: Tokens<'_>) -> Option<Parsed> {
+    let mut args = [0.0; 7];
+    let (w, color) = match parse_numbers(words, &mut args)? {
+        3 => (None, None),
+        4 => (Some(args[3]), None),
+        6 => (None, Some([args[3], args[4], args[5]])),
+        7 => (Some(args[3]), Some([args[4], args[5], args[6]])),
+        _ => return None,
+    };
+    Some(Parsed::Vertex {
+        position: [args[0], args[1], args[2]],
+        w,
+        color,
+    })
+}
+
+fn parse_tex_coord(words: Tokens<'_>) -> Option<Parsed> {
+    let mut uvw = [0.0; 3];
+    let dim = match parse_numbers(words, &mut uvw)? {
+        1 => TextureDim::U,
+        2 => TextureDim::UV,
+        3 => TextureDim::UVW,
+        _ => return None,
+    };
+    Some(Parsed::TexCoord { uvw, dim })
+}
+
+fn parse_normal(mut words: Tokens<'_>) -> Option<Parsed> {
+    // Like the serial reader, any arguments after the first three are ignored.
+    let mut normal = [0.0; 3];
+    for n in normal.iter_mut() {
+        *n = f32::parse(words.next()?)?;
+    }
+    Some(Parsed::Normal(normal))
+}
+
+fn parse_face(words: Tokens<'_>, tuples: &mut Vec<(isize, Option<isize>, Option<isize>)>) -> Option<Parsed> {
+    let start = tuples.len();
+    for word in words {
+        match parse_index_tuple(word) {
+            (Some(p), t, n) => tuples.push((p, t, n)),
+            _ => {
+                tuples.truncate(start);
+                return None;
+            }
+        }
+    }
+    Some(Parsed::Face(start..tuples.len()))
+}
+
+/// Split off the first chunk of about `size` bytes that ends after a complete statement.
+fn split_chunk(input: &[u8], size: usize) -> (&[u8], &[u8]) {
+    let mut end = size;
+    while end < input.len() {
+        let newline = match input[end..].iter().position(|&b| b == b'\n') {
+            Some(newline) => end + newline,
+            None => break,
+        };
+        end = newline + 1;
+
+        // The statement ends with the line unless it is continued. Blank lines may be part of a
+        // continued statement, so chunks only end after a line with content.
+        let line = input[..newline].strip_suffix(b"\r").unwrap_or(&input[..newline]);
+        let line = line.rsplit(|&b| b == b'\n' || b == b'\r').next().unwrap_or(line);
+        match line.trim_ascii_end().last() {
+            Some(b'\\') | None => {}
+            Some(_) => return input.split_at(end),
+        }
+    }
+    (input, &[])
+}
+
+/// Statements of an in-memory `.obj` file, parsed in parallel.
+///
+/// Unlike [`ObjReader`](crate::ObjReader), the text of vertex data and faces is only decoded when
+/// they fail, so [`StatementSource::line`] is stale after any that succeed.
+pub(crate) struct ParallelReader<'a> {
+    rest: &'a [u8],
+    config: LoadConfig,
+    chunks: vec::IntoIter<Chunk<'a>>,
+    chunk: Chunk<'a>,
+    /// The number of lines before the current chunk.
+    line_base: usize,
+    line: String,
+    declared: Declared,
+    buffers: Buffers,
+}
+
+impl<'a> ParallelReader<'a> {
+    pub(crate) fn new(input: &'a [u8], config: LoadConfig) -> Self {
+        ParallelReader {
+            rest: input,
+            config,
+            chunks: Vec::new().into_iter(),
+            chunk: Chunk::empty(),
+            line_base: 0,
+            line: String::new(),
+            declared: Declared::new(&config),
+            buffers: Buffers::default(),
+        }
+    }
+
+    /// Parse the next chunks of the input on all threads.
+    fn read_chunks(&mut self) -> vec::IntoIter<Chunk<'a>> {
+        // Only the empty chunk the reader starts with comes before the start of the input.
+        let first = self.chunk.bytes.is_empty();
+        let mut bytes = Vec::new();
+        while !self.rest.is_empty() && bytes.len() < rayon::current_num_threads() * CHUNKS_PER_THREAD {
+            let (chunk, rest) = split_chunk(self.rest, CHUNK_SIZE);
+            bytes.push(chunk);
+            self.rest = rest;
+        }
+        let config = self.config;
+        bytes
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, bytes)| Chunk::parse(bytes, first && i == 0, &config))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl StatementSource for ParallelReader<'_> {
+    fn config(&self) -> LoadConfig {
+        self.config
+    }
+
+    fn next_statement(&mut self) -> Option<(usize, Result<Statement<'_>, ObjError>)> {
+        let item = loop {
+            if let Some(item) = self.chunk.items.next() {
+                break item;
+            }
+            let next = match self.chunks.next() {
+                Some(next) => next,
+                None if self.rest.is_empty() => return None,
+                None => {
+                    self.chunks = self.read_chunks();
+                    continue;
+                }
+            };
+            self.line_base += mem::replace(&mut self.chunk, next).lines;
+        };
+
+        let idx = self.line_base + item.line_number;
+        let declared = &mut self.declared;
+        let result = match item.parsed {
+            Parsed::Other(text) => {
+                self.line = text;
+                return match parse(&self.line, idx, &self.config, declared, &mut self.buffers) {
+                    Ok(statement) => Some((idx, Ok(statement))),
+                    Err(err) => Some((idx, Err(err.located(None, idx, &self.line)))),
+                };
+            }
+            Parsed::Vertex { position, w, color } => declared
+                .vertex(idx, color.is_some())
+                .map(|()| Statement::Vertex { position, w, color }),
+            Parsed::TexCoord { uvw, dim } => {
+                declared.texture += 1;
+                Ok(Statement::TexCoord { uvw, dim })
+            }
+            Parsed::Normal(normal) => {
+                declared.normal += 1;
+                Ok(Statement::Normal(normal))
+            }
+            Parsed::Face(range) => {
+                let indices = &mut self.buffers.indices;
+                indices.clear();
+                let mut result = Ok(());
+                for &(p, t, n) in &self.chunk.tuples[range] {
+                    match declared.index_tuple(idx, Some(p), t, n) {
+                        Ok(index) => indices.push(index.expect("the positions of parsed index tuples are known")),
+                        Err(err) => {
+                            result = Err(err);
+                            break;
+                        }
+                    }
+                }
+                match result {
+                    Ok(()) => Ok(Statement::Face(indices)),
+                    Err(err) => Err(err),
+                }
+            }
+        };
+
+        match result {
+            Ok(statement) => Some((idx, Ok(statement))),
+            Err(err) => {
+                // The text is only needed to locate the error.
+                let mut lines = LogicalLines::continuing(&self.chunk.bytes[item.span], self.config.encoding);
+                lines.next_into(&mut self.line);
+                Some((idx, Err(err.located(None, idx, &self.line))))
+            }
+        }
+    }
+
+    fn line(&self) -> &str {
+        &self.line
+    }
+
+    fn warnings(&mut self) -> vec::Drain<'_, Warning> {
+        self.declared.warnings.drain(..)
+    }
+
+    fn declare(&mut self, dat: &ObjData) {
+        self.declared.declare(dat);
+    }
+
+    fn forward_reference_errors(&mut self) -> Vec<ObjError> {
+        self.declared.forward_reference_errors()
+    }
+}
@@ -0,0 +1,934 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! A pull parser reading the statements of an `.obj` file one at a time.
+
+use std::{
+    io::{self, BufReader, Read},
+    vec,
+};
+
+use crate::freeform::{CurveRef, CurveTechnique, CurveType, ParamVertex, SurfaceTechnique};
+use crate::include::{substitute_args, IncludePolicy};
+use crate::lex::{parse_f32, parse_index_tuple, parse_isize, Number, Tokens};
+use crate::lines::LogicalLines;
+use crate::obj::{
+    normalize, IndexKind, IndexTuple, LineTuple, LoadConfig, MergingGroup, ObjData, ObjError, TextureDim,
+    DEFAULT_OBJECT,
+};
+use crate::warning::Warning;
+
+/// A statement of an `.obj` file.
+///
+/// Names and lists borrow from the buffers of the [`ObjReader`] that read the statement. Indices of
+/// elements are resolved into 0-based indices of the data declared so far, like in [`ObjData`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statement<'a> {
+    /// A vertex position `v x y z [w] [r g b]`.
+    Vertex {
+        position: [f32; 3],
+        w: Option<f32>,
+        color: Option<[f32; 3]>,
+    },
+    /// Texture coordinates `vt u [v [w]]`, with missing components set to 0.
+    TexCoord { uvw: [f32; 3], dim: TextureDim },
+    /// A normal `vn i j k`.
+    Normal([f32; 3]),
+    /// A parameter space vertex `vp u [v [w]]`.
+    ParamVertex(ParamVertex),
+    /// A face `f v/vt/vn ...`.
+    Face(&'a [IndexTuple]),
+    /// A line `l v/vt ...`.
+    Line(&'a [LineTuple]),
+    /// A point `p v ...`.
+    Point(&'a [usize]),
+    /// An object name `o name`, which is `default` if it's missing.
+    Object(&'a str),
+    /// The names of a group `g name ...`.
+    ///
+    /// The names are separated by whitespace unless [`LoadConfig::single_group_name`] is set.
+    Group(&'a str),
+    /// A material `usemtl name`.
+    UseMtl(Option<&'a str>),
+    /// A material library `mtllib name`, whose name may contain spaces.
+    MtlLib(&'a str),
+    /// A smoothing group `s id`, which is 0 for `s off`.
+    Smoothing(u32),
+    /// A merging group `mg id resolution`, or `None` for `mg off` and `mg 0`.
+    MergingGroup(Option<MergingGroup>),
+    /// `bevel on|off`.
+    Bevel(bool),
+    /// `c_interp on|off`.
+    ColorInterpolation(bool),
+    /// `d_interp on|off`.
+    DissolveInterpolation(bool),
+    /// `lod level`.
+    Lod(u32),
+    /// A texture map `usemap name`, or `None` for `usemap off`.
+    UseMap(Option<&'a str>),
+    /// The whitespace separated names of texture map libraries `maplib name ...`.
+    MapLib(&'a str),
+    /// `shadow_obj name`.
+    ShadowObj(&'a str),
+    /// `trace_obj name`.
+    TraceObj(&'a str),
+    /// `cstype [rat] type`.
+    CurveType { rational: bool, cstype: CurveType },
+    /// `deg u [v]`.
+    Degree(usize, Option<usize>),
+    /// `bmat u matrix`.
+    BasisU(&'a [f32]),
+    /// `bmat v matrix`.
+    BasisV(&'a [f32]),
+    /// `step u [v]`.
+    Step(f32, Option<f32>),
+    /// `ctech technique resolution`.
+    CurveTechnique(CurveTechnique),
+    /// `stech technique resolution`.
+    SurfaceTechnique(SurfaceTechnique),
+    /// A curve `curv u0 u1 v ...`, which starts a free-form element.
+    Curve { range: [f32; 2], vertices: &'a [usize] },
+    /// A 2D curve `curv2 vp ...`, which starts a free-form element.
+    Curve2(&'a [usize]),
+    /// A surface `surf s0 s1 t0 t1 v/vt/vn ...`, which starts a free-form element.
+    Surface {
+        range_u: [f32; 2],
+        range_v: [f32; 2],
+        vertices: &'a [IndexTuple],
+    },
+    /// `parm u value ...` in the body of a free-form element.
+    ParmU(&'a [f32]),
+    /// `parm v value ...` in the body of a free-form element.
+    ParmV(&'a [f32]),
+    /// `trim u0 u1 curv2d ...` in the body of a free-form element.
+    Trim(&'a [CurveRef]),
+    /// `hole u0 u1 curv2d ...` in the body of a free-form element.
+    Hole(&'a [CurveRef]),
+    /// `scrv u0 u1 curv2d ...` in the body of a free-form element.
+    SpecialCurve(&'a [CurveRef]),
+    /// `sp vp ...` in the body of a free-form element.
+    SpecialPoints(&'a [usize]),
+    /// `end`, which closes the current free-form element.
+    End,
+    /// `call filename args ...`, unless [`LoadConfig::include`] is [`IncludePolicy::Ignore`].
+    Call { filename: &'a str, args: &'a str },
+    /// `csh [-]command`, unless [`LoadConfig::include`] is [`IncludePolicy::Ignore`].
+    ShellCommand { command: &'a str, ignore_errors: bool },
+    /// A comment, including the leading `#`.
+    Comment(&'a str),
+    /// A statement that is not in the spec.
+    Unknown { keyword: &'a str, args: &'a str },
+}
+
+/// A positive index past the data declared at the time it was read.
+struct ForwardRef {
+    line_number: usize,
+    kind: IndexKind,
+    index: isize,
+}
+
+/// What the statements read so far declared, which indices are checked against.
+#[derive(Default)]
+pub(crate) struct Declared {
+    position: usize,
+    pub(crate) texture: usize,
+    pub(crate) normal: usize,
+    parameter: usize,
+    curves2: usize,
+    /// Whether the declared vertices have colors.
+    colored: bool,
+    /// Whether a free-form element is open for body statements.
+    element: bool,
+    pub(crate) warnings: Vec<Warning>,
+    /// Forward references to be checked once reading is done, if they are allowed.
+    forward_refs: Option<Vec<ForwardRef>>,
+}
+
+/// Where the loader takes its statements from.
+///
+/// Besides an [`ObjReader`], this is implemented by the parallel reader of in-memory files.
+pub(crate) trait StatementSource {
+    fn config(&self) -> LoadConfig;
+    fn next_statement(&mut self) -> Option<(usize, Result<Statement<'_>, ObjError>)>;
+    fn line(&self) -> &str;
+    fn warnings(&mut self) -> vec::Drain<'_, Warning>;
+    fn declare(&mut self, dat: &ObjData);
+    fn forward_reference_errors(&mut self) -> Vec<ObjError>;
+}
+
+/// Reused storage of the lists borrowed by statements.
+#[derive(Default)]
+pub(crate) struct Buffers {
+    pub(crate) indices: Vec<IndexTuple>,
+    line: Vec<LineTuple>,
+    vertices: Vec<usize>,
+    values: Vec<f32>,
+    refs: Vec<CurveRef>,
+}
+
+/// A pull parser yielding the statements of an `.obj` file without building an [`ObjData`].
+///
+/// Statements borrow from buffers that are reused for the following statement, so the reader is
+/// driven by [`ObjReader::next_statement`] rather than being an `Iterator`. Unlike
+/// [`ObjData::load_buf_with_config`], the reader doesn't open the files named by `call` statements.
+pub struct ObjReader<R> {
+    lines: LogicalLines<BufReader<R>>,
+    pub(crate) config: LoadConfig,
+    /// Arguments substituted for `$1` through `$9` when reading a called file.
+    args: Vec<String>,
+    line: String,
+    line_number: usize,
+    done: bool,
+    declared: Declared,
+    buffers: Buffers,
+}
+
+impl<R: Read> ObjReader<R> {
+    /// Create a reader of the given input.
+    pub fn new(input: R, config: LoadConfig) -> Self {
+        Self::with_args(input, config, Vec::new())
+    }
+
+    pub(crate) fn with_args(input: R, config: LoadConfig, args: Vec<String>) -> Self {
+        ObjReader {
+            lines: LogicalLines::new(BufReader::new(input), config.encoding),
+            config,
+            args,
+            line: String::new(),
+            line_number: 0,
+            done: false,
+            declared: Declared::new(&config),
+            buffers: Buffers::default(),
+        }
+    }
+
+    /// Read the next statement, skipping blank lines.
+    ///
+    /// Returns the 1-based number of the line where the statement begins together with the
+    /// statement, or `None` at the end of the input. Reading stops after an I/O error, while other
+    /// errors only skip the offending statement.
+    pub fn next_statement(&mut self) -> Option<(usize, Result<Statement<'_>, ObjError>)> {
+        loop {
+            if self.done {
+                return None;
+            }
+            let (line_number, result) = match self.lines.next_into(&mut self.line) {
+                Some(next) => next,
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            self.line_number = line_number;
+            if let Err(err) = result {
+                self.done = true;
+                let err = io::Error::new(io::ErrorKind::InvalidData, format!("failed to readline {}", err));
+                return Some((line_number, Err(ObjError::Io(err))));
+            }
+            if !self.args.is_empty() {
+                self.line = substitute_args(&self.line, &self.args);
+            }
+            if !self.skipped() {
+                break;
+            }
+        }
+
+        let (line, line_number) = (&self.line, self.line_number);
+        let result = parse(line, line_number, &self.config, &mut self.declared, &mut self.buffers);
+        Some((line_number, result.map_err(|err| err.located(None, line_number, line))))
+    }
+
+    /// Whether the current line holds nothing to be parsed.
+    fn skipped(&self) -> bool {
+        match Tokens::new(&self.line).next() {
+            Some("call") | Some("csh") => self.config.include == IncludePolicy::Ignore,
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    /// Text of the statement that was read last, with continued lines joined.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// 1-based number of the line where the statement that was read last begins.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Take the problems that were fixed up in the statements read so far.
+    pub fn warnings(&mut self) -> vec::Drain<'_, Warning> {
+        self.declared.warnings.drain(..)
+    }
+
+    /// Check the forward references allowed by [`LoadConfig::forward_references`] against all
+    /// the data that was read.
+    ///
+    /// This is meant to be called once the end of the input is reached.
+    pub fn forward_reference_errors(&mut self) -> Vec<ObjError> {
+        self.declared.forward_reference_errors()
+    }
+
+    /// Continue indexing after the given data, into which a called file has been spliced.
+    pub(crate) fn declare(&mut self, dat: &ObjData) {
+        self.declared.declare(dat);
+    }
+}
+
+impl<R: Read> StatementSource for ObjReader<R> {
+    fn config(&self) -> LoadConfig {
+        self.config
+    }
+
+    fn next_statement(&mut self) -> Option<(usize, Result<Statement<'_>, ObjError>)> {
+        ObjReader::next_statement(self)
+    }
+
+    fn line(&self) -> &str {
+        ObjReader::line(self)
+    }
+
+    fn warnings(&mut self) -> vec::Drain<'_, Warning> {
+        ObjReader::warnings(self)
+    }
+
+    fn declare(&mut self, dat: &ObjData) {
+        ObjReader::declare(self, dat)
+    }
+
+    fn forward_reference_errors(&mut self) -> Vec<ObjError> {
+        ObjReader::forward_reference_errors(self)
+    }
+}
+
+impl Declared {
+    pub(crate) fn new(config: &LoadConfig) -> Self {
+        Declared {
+            forward_refs: Some(Vec::new()).filter(|_| config.forward_references),
+            ..Declared::default()
+        }
+    }
+
+    /// Check the forward references against all the data that was declared.
+    pub(crate) fn forward_reference_errors(&mut self) -> Vec<ObjError> {
+        let refs = self.forward_refs.take().unwrap_or_default();
+        refs.into_iter()
+            .filter_map(
+                |ForwardRef {
+                     line_number,
+                     kind,
+                     index,
+                 }| {
+                    let len = match kind {
+                        IndexKind::Position => self.position,
+                        IndexKind::Texture => self.texture,
+                        IndexKind::Normal => self.normal,
+                        IndexKind::Parameter => self.parameter,
+                        IndexKind::Curve2 => self.curves2,
+                    };
+                    Some(ObjError::IndexOutOfRange {
+                        line_number,
+                        kind,
+                        index,
+                        len,
+                    })
+                    .filter(|_| index as usize > len)
+                },
+            )
+            .collect()
+    }
+
+    /// Continue indexing after the given data.
+    pub(crate) fn declare(&mut self, dat: &ObjData) {
+        self.position = dat.position.len();
+        self.texture = dat.texture.len();
+        self.normal = dat.normal.len();
+        self.parameter = dat.parameter.len();
+        self.curves2 = dat.free_form.curves2.len();
+        self.colored = !dat.color.is_empty();
+    }
+
+    /// Declare a vertex position, checking that it has colors like the ones before it.
+    pub(crate) fn vertex(&mut self, line_number: usize, colored: bool) -> Result<(), ObjError> {
+        // Colors are either given for every vertex or not at all, so that they stay aligned with
+        // the positions.
+        if self.position > 0 && colored != self.colored {
+            return Err(ObjError::InconsistentVertexColors { line_number });
+        }
+        self.position += 1;
+        self.colored = colored;
+        Ok(())
+    }
+
+    /// Convert a non-zero index into a 0-based index of one of the `len` items declared so far.
+    fn index(&mut self, line_number: usize, kind: IndexKind, index: isize, len: usize) -> Result<usize, ObjError> {
+        match (normalize(index, len), &mut self.forward_refs) {
+            (Some(i), _) if i < len => Ok(i),
+            (Some(i), Some(refs)) if index > 0 => {
+                refs.push(ForwardRef {
+                    line_number,
+                    kind,
+                    index,
+                });
+                Ok(i)
+            }
+            _ => Err(ObjError::IndexOutOfRange {
+                line_number,
+                kind,
+                index,
+                len,
+            }),
+        }
+    }
+
+    /// Parse a 1-based or negative relative reference to one of `len` previously declared items.
+    fn parse_index(&mut self, line_number: usize, arg: &str, kind: IndexKind, len: usize) -> Result<usize, ObjError> {
+        match parse_isize(arg.as_bytes()) {
+            Some(0) => Err(ObjError::ZeroVertexNumber { line_number }),
+            Some(idx) => self.index(line_number, kind, idx, len),
+            None => Err(argument_list_failure(line_number, Some(arg).into_iter())),
+        }
+    }
+
+    /// Parse the position indices of a statement into `out`.
+    fn parse_vertices(&mut self, line_number: usize, args: Tokens<'_>, out: &mut Vec<usize>) -> Result<(), ObjError> {
+        out.clear();
+        for arg in args {
+            out.push(self.parse_index(line_number, arg, IndexKind::Position, self.position)?);
+        }
+        Ok(())
+    }
+
+    /// Parse the parameter space vertex indices of a statement into `out`.
+    fn parse_parameters(&mut self, line_number: usize, args: Tokens<'_>, out: &mut Vec<usize>) -> Result<(), ObjError> {
+        out.clear();
+        for arg in args {
+            out.push(self.parse_index(line_number, arg, IndexKind::Parameter, self.parameter)?);
+        }
+        Ok(())
+    }
+
+    fn parse_group(&mut self, line_number: usize, group: &str) -> Result<IndexTuple, ObjError> {
+        let (p, t, n) = parse_index_tuple(group);
+        self.index_tuple(line_number, p, t, n)?
+            .ok_or_else(|| ObjError::MalformedFaceGroup {
+                line_number,
+                group: String::from(group),
+            })
+    }
+
+    /// Resolve the references of a `v/vt/vn` index tuple, which is malformed if the position is
+    /// missing.
+    pub(crate) fn index_tuple(
+        &mut self,
+        line_number: usize,
+        p: Option<isize>,
+        t: Option<isize>,
+        n: Option<isize>,
+    ) -> Result<Option<IndexTuple>, ObjError> {
+        // Zero indices are ignored with a warning for texture and normal indices.
+        if t == Some(0) {
+            self.warnings.push(Warning::ZeroTextureIndex { line_number });
+        }
+        if n == Some(0) {
+            self.warnings.push(Warning::ZeroNormalIndex { line_number });
+        }
+        let p = match p {
+            Some(0) => return Err(ObjError::ZeroVertexNumber { line_number }),
+            Some(p) => self.index(line_number, IndexKind::Position, p, self.position)?,
+            None => return Ok(None),
+        };
+        let (texture, normal) = (self.texture, self.normal);
+        let mut index = |idx, kind, len| match idx {
+            Some(0) | None => Ok(None),
+            Some(idx) => self.index(line_number, kind, idx, len).map(Some),
+        };
+        let t = index(t, IndexKind::Texture, texture)?;
+        let n = index(n, IndexKind::Normal, normal)?;
+        Ok(Some(IndexTuple(p, t, n)))
+    }
+
+    /// Parse the index tuples of a statement into `out`.
+    fn parse_groups(
+        &mut self,
+        line_number: usize,
+        groups: Tokens<'_>,
+        out: &mut Vec<IndexTuple>,
+    ) -> Result<(), ObjError> {
+        out.clear();
+        for g in groups {
+            out.push(self.parse_group(line_number, g)?);
+        }
+        Ok(())
+    }
+
+    /// Parse the `u0 u1 curv2d` triples of the `trim`, `hole` and `scrv` statements into `out`.
+    fn parse_curve_refs(
+        &mut self,
+        line_number: usize,
+        mut args: Tokens<'_>,
+        out: &mut Vec<CurveRef>,
+    ) -> Result<(), ObjError> {
+        let list = args.clone();
+        out.clear();
+        while let Some(range) = parse_range(&mut args) {
+            let curve = match args.next() {
+                Some(curve) => self.parse_index(line_number, curve, IndexKind::Curve2, self.curves2)?,
+                None => break,
+            };
+            out.push(CurveRef { range, curve });
+        }
+        if out.is_empty() || out.len() * 3 != list.clone().count() {
+            return Err(argument_list_failure(line_number, list));
+        }
+        Ok(())
+    }
+
+    /// Check that a body statement belongs to an open free-form element.
+    fn element(&self, line_number: usize, command: &str) -> Result<(), ObjError> {
+        if self.element {
+            Ok(())
+        } else {
+            Err(ObjError::FreeFormStatementOutsideElement {
+                line_number,
+                command: command.to_string(),
+            })
+        }
+    }
+}
+
+fn argument_list_failure<'b>(line_number: usize, args: impl Iterator<Item = &'b str>) -> ObjError {
+    ObjError::ArgumentListFailure {
+        line_number,
+        list: args.collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Parse all remaining arguments of a statement into `out` and return how many were found.
+///
+/// Fails if any of the arguments can't be parsed or if there are more than `N` of them. A trailing
+/// comment is ignored.
+fn parse_args<T: Number, const N: usize>(
+    line_number: usize,
+    args: Tokens,
+    out: &mut [T; N],
+) -> Result<usize, ObjError> {
+    let mut count = 0;
+    for arg in args.clone().before_comment() {
+        match (out.get_mut(count), T::parse(arg)) {
+            (Some(slot), Some(value)) => *slot = value,
+            _ => return Err(argument_list_failure(line_number, args)),
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Parse the leading arguments of a statement into `out` until one can't be parsed, returning how many
+/// were parsed.
+fn parse_leading_args<const N: usize>(args: Tokens<'_>, out: &mut [f32; N]) -> usize {
+    let mut count = 0;
+    for (slot, arg) in out.iter_mut().zip(args.before_comment()) {
+        match parse_f32(arg) {
+            Some(value) => *slot = value,
+            None => break,
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Parse all remaining arguments of a statement before a trailing comment into `out`.
+fn parse_list(line_number: usize, args: Tokens<'_>, out: &mut Vec<f32>) -> Result<(), ObjError> {
+    out.clear();
+    for arg in args.clone().before_comment() {
+        out.push(parse_f32(arg).ok_or_else(|| argument_list_failure(line_number, args.clone()))?);
+    }
+    Ok(())
+}
+
+/// Parse the single `on` or `off` argument of a display attribute statement.
+fn parse_switch(line_number: usize, mut args: Tokens) -> Result<bool, ObjError> {
+    let list = args.clone();
+    match (args.next(), args.next()) {
+        (Some("on"), None) => Ok(true),
+        (Some("off"), None) => Ok(false),
+        _ => Err(argument_list_failure(line_number, list)),
+    }
+}
+
+/// Parse a pair of starting and ending parameter values of a free-form statement.
+fn parse_range(args: &mut Tokens) -> Option<[f32; 2]> {
+    match (args.next().and_then(parse_f32), args.next().and_then(parse_f32)) {
+        (Some(start), Some(end)) => Some([start, end]),
+        _ => None,
+    }
+}
+
+fn parse_three(line_number: usize, n0: Option<&str>, n1: Option<&str>, n2: Option<&str>) -> Result<[f32; 3], ObjError> {
+    let (n0, n1, n2) = match (n0, n1, n2) {
+        (Some(n0), Some(n1), Some(n2)) => (n0, n1, n2),
+        _ => {
+            return Err(ObjError::ArgumentListFailure {
+                line_number,
+                list: format!("{:?} {:?} {:?}", n0, n1, n2),
+            });
+        }
+    };
+    let normal = match (parse_f32(n0), parse_f32(n1), parse_f32(n2)) {
+        (Some(n0), Some(n1), Some(n2)) => [n0, n1, n2],
+        _ => {
+            return Err(ObjError::ArgumentListFailure {
+                line_number,
+                list: format!("{:?} {:?} {:?}", n0, n1, n2),
+            });
+        }
+    };
+    Ok(normal)
+}
+
+/// Parse a single statement, which is neither blank nor skipped.
+pub(crate) fn parse<'a>(
+    line: &'a str,
+    idx: usize,
+    config: &LoadConfig,
+    declared: &mut Declared,
+    buffers: &'a mut Buffers,
+) -> Result<Statement<'a>, ObjError> {
+    let mut words = Tokens::new(line);
+    let keyword = match words.next() {
+        Some(keyword) => keyword,
+        None => return Ok(Statement::Comment(line)),
+    };
+    let statement = match keyword {
+        "v" => {
+            let mut args = [0.0; 7];
+            let count = if config.strict {
+                parse_args(idx, words.clone(), &mut args)?
+            } else {
+                // Arguments after those of the longest valid vertex are ignored.
+                let count = match parse_leading_args(words.clone(), &mut args) {
+                    5 => 4,
+                    count => count,
+                };
+                let extra = words.clone().before_comment().skip(count).collect::<Vec<_>>();
+                if count >= 3 && !extra.is_empty() {
+                    declared.warnings.push(Warning::ExtraVertexArguments {
+                        line_number: idx,
+                        args: extra.join(" "),
+                    });
+                }
+                count
+            };
+            let (w, color) = match count {
+                3 => (None, None),
+                4 => (Some(args[3]), None),
+                6 => (None, Some([args[3], args[4], args[5]])),
+                7 => (Some(args[3]), Some([args[4], args[5], args[6]])),
+                _ => return Err(argument_list_failure(idx, words)),
+            };
+            declared.vertex(idx, color.is_some())?;
+            Statement::Vertex {
+                position: [args[0], args[1], args[2]],
+                w,
+                color,
+            }
+        }
+        "vt" => {
+            let mut args = [0.0; 3];
+            let dim = match parse_args(idx, words.clone(), &mut args)? {
+                1 => TextureDim::U,
+                2 => TextureDim::UV,
+                3 => TextureDim::UVW,
+                _ => return Err(argument_list_failure(idx, words)),
+            };
+            declared.texture += 1;
+            Statement::TexCoord { uvw: args, dim }
+        }
+        "vn" => {
+            let (n0, n1, n2) = (words.next(), words.next(), words.next());
+            let normal = parse_three(idx, n0, n1, n2)?;
+            declared.normal += 1;
+            Statement::Normal(normal)
+        }
+        "vp" => {
+            let mut args = [0.0; 3];
+            let vp = match parse_args(idx, words.clone(), &mut args)? {
+                1 => ParamVertex(args[0], None, None),
+                2 => ParamVertex(args[0], Some(args[1]), None),
+                3 => ParamVertex(args[0], Some(args[1]), Some(args[2])),
+                _ => return Err(argument_list_failure(idx, words)),
+            };
+            declared.parameter += 1;
+            Statement::ParamVertex(vp)
+        }
+        "f" => {
+            declared.parse_groups(idx, words, &mut buffers.indices)?;
+            Statement::Face(&buffers.indices)
+        }
+        "l" => {
+            buffers.line.clear();
+            for g in words {
+                let ituple = declared.parse_group(idx, g)?;
+                if ituple.2.is_some() {
+                    return Err(ObjError::LineHasNormalIndex { line_number: idx });
+                }
+                buffers.line.push(ituple.into());
+            }
+            Statement::Line(&buffers.line)
+        }
+        "p" => {
+            declared.parse_vertices(idx, words, &mut buffers.vertices)?;
+            Statement::Point(&buffers.vertices)
+        }
+        "o" => Statement::Object(match words.remainder() {
+            "" => DEFAULT_OBJECT,
+            name => name,
+        }),
+        "g" if config.single_group_name => Statement::Group(line.trim_start()[1..].trim()),
+        "g" => Statement::Group(words.remainder()),
+        "mtllib" => {
+            // Obj strictly does not allow spaces in filenames, but everyone does it anyway, e.g.
+            // Blender writes "mtllib Some File.mtl". So the whole rest of the line is the name.
+            match words.remainder() {
+                "" => return Err(ObjError::MissingMTLName { line_number: idx }),
+                name => Statement::MtlLib(name),
+            }
+        }
+        "usemtl" => Statement::UseMtl(words.next()),
+        "s" => {
+            let args = words.clone();
+            let mut words = words.before_comment();
+            let smoothing_group = match (words.next(), words.next()) {
+                (Some("off"), None) => Some(0),
+                (Some(id), None) => id.parse().ok(),
+                _ => None,
+            };
+            match smoothing_group {
+                Some(smoothing_group) => Statement::Smoothing(smoothing_group),
+                // Without a valid group number, smoothing is turned off like by `s off`. This holds in
+                // strict mode too, since malformed `s` statements used to be ignored altogether.
+                None => {
+                    declared.warnings.push(Warning::MalformedSmoothingGroup {
+                        line_number: idx,
+                        args: args.remainder().to_string(),
+                    });
+                    Statement::Smoothing(0)
+                }
+            }
+        }
+        "bevel" => Statement::Bevel(parse_switch(idx, words)?),
+        "c_interp" => Statement::ColorInterpolation(parse_switch(idx, words)?),
+        "d_interp" => Statement::DissolveInterpolation(parse_switch(idx, words)?),
+        "lod" => {
+            let mut level = [0];
+            if parse_args(idx, words.clone(), &mut level)? != 1 {
+                return Err(argument_list_failure(idx, words));
+            }
+            Statement::Lod(level[0])
+        }
+        "usemap" => {
+            let args = words.clone();
+            match (words.next(), words.next()) {
+                (Some("off"), None) => Statement::UseMap(None),
+                (Some(name), None) => Statement::UseMap(Some(name)),
+                _ => return Err(argument_list_failure(idx, args)),
+            }
+        }
+        "maplib" => match words.remainder() {
+            "" => return Err(argument_list_failure(idx, None.into_iter())),
+            names => Statement::MapLib(names),
+        },
+        "shadow_obj" | "trace_obj" => {
+            let args = words.clone();
+            let name = match (words.next(), words.next()) {
+                (Some(name), None) => name,
+                _ => return Err(argument_list_failure(idx, args)),
+            };
+            match keyword {
+                "shadow_obj" => Statement::ShadowObj(name),
+                _ => Statement::TraceObj(name),
+            }
+        }
+        "mg" => {
+            let args = words.clone();
+            let merging_group = match (words.next(), words.next(), words.next()) {
+                (Some("off"), None, None) | (Some("0"), _, None) => None,
+                (Some(id), Some(resolution), None) => match (id.parse(), resolution.parse()) {
+                    (Ok(id), Ok(resolution)) => Some(MergingGroup { id, resolution }),
+                    _ => return Err(argument_list_failure(idx, args)),
+                },
+                _ => return Err(argument_list_failure(idx, args)),
+            };
+            Statement::MergingGroup(merging_group)
+        }
+        "cstype" => {
+            let args = words.clone();
+            let (rational, cstype) = match (words.next(), words.next(), words.next()) {
+                (Some("rat"), Some(cstype), None) => (true, cstype),
+                (Some(cstype), None, None) => (false, cstype),
+                _ => return Err(argument_list_failure(idx, args)),
+            };
+            let cstype = cstype.parse().map_err(|_| argument_list_failure(idx, args))?;
+            Statement::CurveType { rational, cstype }
+        }
+        "deg" => {
+            let mut args = [0; 2];
+            match parse_args(idx, words.clone(), &mut args)? {
+                1 => Statement::Degree(args[0], None),
+                2 => Statement::Degree(args[0], Some(args[1])),
+                _ => return Err(argument_list_failure(idx, words)),
+            }
+        }
+        "bmat" => {
+            let args = words.clone();
+            let dir = words.next();
+            match dir {
+                Some("u") | Some("v") => parse_list(idx, words, &mut buffers.values)?,
+                _ => return Err(argument_list_failure(idx, args)),
+            }
+            match dir {
+                Some("u") => Statement::BasisU(&buffers.values),
+                _ => Statement::BasisV(&buffers.values),
+            }
+        }
+        "step" => {
+            let mut args = [0.0; 2];
+            match parse_args(idx, words.clone(), &mut args)? {
+                1 => Statement::Step(args[0], None),
+                2 => Statement::Step(args[0], Some(args[1])),
+                _ => return Err(argument_list_failure(idx, words)),
+            }
+        }
+        "ctech" => {
+            let args = words.clone();
+            let technique = words.next();
+            let mut values = [0.0; 2];
+            Statement::CurveTechnique(match (technique, parse_args(idx, words, &mut values)?) {
+                (Some("cparm"), 1) => CurveTechnique::Parametric { res: values[0] },
+                (Some("cspace"), 1) => CurveTechnique::Spatial { max_length: values[0] },
+                (Some("curv"), 2) => CurveTechnique::Curvature {
+                    max_distance: values[0],
+                    max_angle: values[1],
+                },
+                _ => return Err(argument_list_failure(idx, args)),
+            })
+        }
+        "stech" => {
+            let args = words.clone();
+            let technique = words.next();
+            let mut values = [0.0; 2];
+            Statement::SurfaceTechnique(match (technique, parse_args(idx, words, &mut values)?) {
+                (Some("cparma"), 2) => SurfaceTechnique::ParametricA {
+                    res_u: values[0],
+                    res_v: values[1],
+                },
+                (Some("cparmb"), 1) => SurfaceTechnique::ParametricB { res: values[0] },
+                (Some("cspace"), 1) => SurfaceTechnique::Spatial { max_length: values[0] },
+                (Some("curv"), 2) => SurfaceTechnique::Curvature {
+                    max_distance: values[0],
+                    max_angle: values[1],
+                },
+                _ => return Err(argument_list_failure(idx, args)),
+            })
+        }
+        "curv" => {
+            let list = words.clone();
+            let range = parse_range(&mut words).ok_or_else(|| argument_list_failure(idx, list))?;
+            declared.parse_vertices(idx, words, &mut buffers.vertices)?;
+            declared.element = true;
+            Statement::Curve {
+                range,
+                vertices: &buffers.vertices,
+            }
+        }
+        "curv2" => {
+            declared.parse_parameters(idx, words, &mut buffers.vertices)?;
+            declared.element = true;
+            declared.curves2 += 1;
+            Statement::Curve2(&buffers.vertices)
+        }
+        "surf" => {
+            let list = words.clone();
+            let (range_u, range_v) = match (parse_range(&mut words), parse_range(&mut words)) {
+                (Some(range_u), Some(range_v)) => (range_u, range_v),
+                _ => return Err(argument_list_failure(idx, list)),
+            };
+            declared.parse_groups(idx, words, &mut buffers.indices)?;
+            declared.element = true;
+            Statement::Surface {
+                range_u,
+                range_v,
+                vertices: &buffers.indices,
+            }
+        }
+        "parm" => {
+            let args = words.clone();
+            let dir = words.next();
+            parse_list(idx, words, &mut buffers.values)?;
+            declared.element(idx, keyword)?;
+            match dir {
+                Some("u") => Statement::ParmU(&buffers.values),
+                Some("v") => Statement::ParmV(&buffers.values),
+                _ => return Err(argument_list_failure(idx, args)),
+            }
+        }
+        "trim" | "hole" | "scrv" => {
+            declared.parse_curve_refs(idx, words, &mut buffers.refs)?;
+            declared.element(idx, keyword)?;
+            match keyword {
+                "trim" => Statement::Trim(&buffers.refs),
+                "hole" => Statement::Hole(&buffers.refs),
+                _ => Statement::SpecialCurve(&buffers.refs),
+            }
+        }
+        "sp" => {
+            declared.parse_parameters(idx, words, &mut buffers.vertices)?;
+            declared.element(idx, keyword)?;
+            Statement::SpecialPoints(&buffers.vertices)
+        }
+        "end" => {
+            declared.element(idx, keyword)?;
+            declared.element = false;
+            Statement::End
+        }
+        "call" => {
+            let filename = words
+                .next()
+                .ok_or_else(|| argument_list_failure(idx, None.into_iter()))?;
+            Statement::Call {
+                filename,
+                args: words.remainder(),
+            }
+        }
+        "csh" => {
+            let command = line.trim_start()[3..].trim();
+            let (command, ignore_errors) = match command.strip_prefix('-') {
+                Some(command) => (command, true),
+                None => (command, false),
+            };
+            if command.is_empty() {
+                return Err(argument_list_failure(idx, words));
+            }
+            Statement::ShellCommand { command, ignore_errors }
+        }
+        _ if keyword.starts_with('#') => Statement::Comment(line),
+        _ => Statement::Unknown {
+            keyword,
+            args: words.remainder(),
+        },
+    };
+    Ok(statement)
+}
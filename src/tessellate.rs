@@ -0,0 +1,669 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Tessellation of free-form surfaces into polygons.
+
+use crate::freeform::{CurveRef, CurveTechnique, CurveType, FreeFormAttributes, Surface, SurfaceTechnique};
+use crate::obj::{Group, IndexTuple, ObjData, ObjError, SimplePolygon};
+
+/// Number of segments between consecutive knots when no approximation technique is set.
+const DEFAULT_RESOLUTION: usize = 8;
+
+/// Upper bound on the number of segments between consecutive knots.
+const MAX_RESOLUTION: usize = 1024;
+
+/// Upper bound on the number of vertices generated for all surfaces together.
+const MAX_VERTICES: usize = 1 << 24;
+
+/// Basis matrix of cardinal splines as defined by the `bmat` statement: one row of power basis
+/// coefficients per control point.
+const CARDINAL_MATRIX: [f32; 16] = [
+    0.0, -0.5, 1.0, -0.5, //
+    1.0, 0.0, -2.5, 1.5, //
+    0.0, 0.5, 2.0, -1.5, //
+    0.0, 0.0, -0.5, 0.5,
+];
+
+/// The approximation technique used to pick the number of samples along one direction.
+#[derive(Clone, Copy)]
+enum Approximation {
+    Parametric(f32),
+    Spatial(f32),
+    Curvature(f32, f32),
+}
+
+impl Approximation {
+    fn from_curve(technique: Option<CurveTechnique>) -> Option<Self> {
+        technique.map(|technique| match technique {
+            CurveTechnique::Parametric { res } => Approximation::Parametric(res),
+            CurveTechnique::Spatial { max_length } => Approximation::Spatial(max_length),
+            CurveTechnique::Curvature {
+                max_distance,
+                max_angle,
+            } => Approximation::Curvature(max_distance, max_angle),
+        })
+    }
+
+    fn from_surface(technique: Option<SurfaceTechnique>, dir: usize) -> Option<Self> {
+        technique.map(|technique| match technique {
+            SurfaceTechnique::ParametricA { res_u, res_v } => {
+                Approximation::Parametric(if dir == 0 { res_u } else { res_v })
+            }
+            SurfaceTechnique::ParametricB { res } => Approximation::Parametric(res),
+            SurfaceTechnique::Spatial { max_length } => Approximation::Spatial(max_length),
+            SurfaceTechnique::Curvature {
+                max_distance,
+                max_angle,
+            } => Approximation::Curvature(max_distance, max_angle),
+        })
+    }
+
+    /// The number of segments needed between consecutive knots.
+    ///
+    /// The spatial and curvature techniques are estimated from the control polygons, which bound
+    /// the length and total turning of the curve they define.
+    fn resolution(approximation: Option<Self>, spans: usize, polygons: &[Vec<[f32; 3]>]) -> usize {
+        let total = |segments: f32| (segments / spans.max(1) as f32).ceil();
+        let res = match approximation {
+            None => return DEFAULT_RESOLUTION,
+            Some(Approximation::Parametric(res)) => res.ceil(),
+            Some(Approximation::Spatial(max_length)) => {
+                let length = polygons.iter().map(|p| length(p)).fold(0.0, f32::max);
+                total(length / max_length)
+            }
+            Some(Approximation::Curvature(max_distance, max_angle)) => {
+                let segments = polygons
+                    .iter()
+                    .map(|p| {
+                        let turning = turning(p);
+                        if turning <= 0.0 {
+                            return 1.0;
+                        }
+                        // Approximate the polygon by a circular arc, whose segments deviate from
+                        // it by its sagitta `r (1 - cos(angle / 2))`.
+                        let radius = length(p) / turning;
+                        let distance_angle = if max_distance >= radius {
+                            std::f32::consts::PI
+                        } else {
+                            2.0 * (1.0 - max_distance / radius).acos()
+                        };
+                        turning / distance_angle.min(max_angle.to_radians())
+                    })
+                    .fold(0.0, f32::max);
+                total(segments)
+            }
+        };
+        if res.is_nan() {
+            DEFAULT_RESOLUTION
+        } else {
+            (res as usize).clamp(1, MAX_RESOLUTION)
+        }
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalized(a: [f32; 3]) -> Option<[f32; 3]> {
+    let len = dot(a, a).sqrt();
+    if len > 0.0 {
+        Some([a[0] / len, a[1] / len, a[2] / len])
+    } else {
+        None
+    }
+}
+
+/// Total length of a polygon.
+fn length(points: &[[f32; 3]]) -> f32 {
+    points
+        .windows(2)
+        .map(|w| dot(sub(w[1], w[0]), sub(w[1], w[0])).sqrt())
+        .sum()
+}
+
+/// Sum of the angles between consecutive edges of a polygon in radians.
+fn turning(points: &[[f32; 3]]) -> f32 {
+    points
+        .windows(3)
+        .filter_map(|w| {
+            let (a, b) = (normalized(sub(w[1], w[0]))?, normalized(sub(w[2], w[1]))?);
+            Some(dot(a, b).clamp(-1.0, 1.0).acos())
+        })
+        .sum()
+}
+
+/// Whether `p` lies inside the closed polygon `points` according to the even-odd rule.
+fn contains(points: &[[f32; 2]], p: [f32; 2]) -> bool {
+    let mut inside = false;
+    let mut j = points.len().wrapping_sub(1);
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a[1] > p[1]) != (b[1] > p[1]) && p[0] < (b[0] - a[0]) * (p[1] - a[1]) / (b[1] - a[1]) + a[0] {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Evaluates the basis functions along one parametric direction of a curve or surface.
+struct Basis {
+    degree: usize,
+    /// Global parameter values given by `parm`.
+    knots: Vec<f32>,
+    /// Number of control points along this direction.
+    count: usize,
+    /// Basis matrix and step of all types but B-splines.
+    matrix: Option<(Vec<f32>, usize)>,
+}
+
+impl Basis {
+    /// Set up the basis of a curve or surface with the given number of control points in total.
+    fn new(
+        attributes: &FreeFormAttributes,
+        degree: usize,
+        basis: &Option<Vec<f32>>,
+        step: Option<f32>,
+        knots: &[f32],
+        vertices: usize,
+    ) -> Result<Self, String> {
+        let cstype = attributes.cstype.ok_or("missing `cstype`")?;
+        if degree == 0 {
+            return Err("degree must be at least 1".to_string());
+        }
+        // The degree and step are bounded before they size any matrix. A basis needs more control
+        // points than its degree, and a step beyond the control points can't match them.
+        if degree >= vertices || degree > MAX_RESOLUTION {
+            return Err(format!("degree {} is too high for {} control points", degree, vertices));
+        }
+        let step = match step {
+            Some(step) if step > vertices as f32 || step > MAX_RESOLUTION as f32 => {
+                return Err(format!("step {} is too large for {} control points", step, vertices))
+            }
+            step => step,
+        };
+        if knots.windows(2).any(|w| w[0] > w[1]) {
+            return Err("parameter values must be increasing".to_string());
+        }
+
+        let matrix = match cstype {
+            CurveType::BSpline => None,
+            CurveType::Bezier => Some((bezier_matrix(degree), degree)),
+            CurveType::Cardinal if degree == 3 => Some((CARDINAL_MATRIX.to_vec(), 1)),
+            CurveType::Cardinal => return Err("cardinal splines must have degree 3".to_string()),
+            CurveType::Taylor => {
+                let mut identity = vec![0.0; (degree + 1) * (degree + 1)];
+                for i in 0..=degree {
+                    identity[i * (degree + 1) + i] = 1.0;
+                }
+                Some((identity, degree + 1))
+            }
+            CurveType::BasisMatrix => {
+                let matrix = basis.as_ref().ok_or("missing `bmat`")?;
+                if matrix.len() != (degree + 1) * (degree + 1) {
+                    return Err("basis matrix size doesn't match the degree".to_string());
+                }
+                match step {
+                    Some(step) if step >= 1.0 => Some((matrix.clone(), step as usize)),
+                    _ => return Err("missing or invalid `step`".to_string()),
+                }
+            }
+        };
+
+        let count = match matrix {
+            None if knots.len() / 2 > degree => knots.len() - degree - 1,
+            Some((_, step)) if knots.len() >= 2 => (knots.len() - 2)
+                .checked_mul(step)
+                .and_then(|count| count.checked_add(degree + 1))
+                .ok_or("too many parameter values")?,
+            _ => return Err("not enough parameter values".to_string()),
+        };
+
+        Ok(Basis {
+            degree,
+            knots: knots.to_vec(),
+            count,
+            matrix,
+        })
+    }
+
+    /// The parameter values at which the polynomial pieces meet.
+    fn breakpoints(&self) -> &[f32] {
+        match self.matrix {
+            None => &self.knots[self.degree..=self.count],
+            Some(_) => &self.knots,
+        }
+    }
+
+    /// The parameter values to sample within `range` using `res` segments between knots.
+    fn samples(&self, range: [f32; 2], res: usize) -> Vec<f32> {
+        let (start, end) = (range[0].min(range[1]), range[0].max(range[1]));
+        let mut bounds = vec![start];
+        bounds.extend(self.breakpoints().iter().copied().filter(|&k| k > start && k < end));
+        bounds.push(end);
+        bounds.dedup();
+
+        let mut samples = Vec::with_capacity(bounds.len() * res);
+        for w in bounds.windows(2) {
+            samples.extend((0..res).map(|i| w[0] + (w[1] - w[0]) * i as f32 / res as f32));
+        }
+        samples.push(end);
+        if range[0] > range[1] {
+            samples.reverse();
+        }
+        samples
+    }
+
+    /// Number of spans between the knots of this basis.
+    fn spans(&self) -> usize {
+        self.breakpoints().len().saturating_sub(1)
+    }
+
+    /// Append the control point indices and values of the non-zero basis functions at `u`.
+    fn eval(&self, u: f32, out: &mut Vec<(usize, f32)>) {
+        out.clear();
+        let d = self.degree;
+        match self.matrix {
+            Some((ref matrix, step)) => {
+                let segments = self.knots.len() - 1;
+                let s = self.knots[1..segments].iter().take_while(|&&k| k <= u).count();
+                let (k0, k1) = (self.knots[s], self.knots[s + 1]);
+                let t = if k1 > k0 { (u - k0) / (k1 - k0) } else { 0.0 };
+                for i in 0..=d {
+                    let (mut value, mut power) = (0.0, 1.0);
+                    for j in 0..=d {
+                        value += matrix[i * (d + 1) + j] * power;
+                        power *= t;
+                    }
+                    out.push((s * step + i, value));
+                }
+            }
+            None => {
+                // Cox-de Boor recursion as given by "The NURBS Book", algorithm A2.2.
+                let knots = &self.knots;
+                let u = u.max(knots[d]).min(knots[self.count]);
+                let mut span = d;
+                while span < self.count - 1 && knots[span + 1] <= u {
+                    span += 1;
+                }
+                let mut left = vec![0.0; d + 1];
+                let mut right = vec![0.0; d + 1];
+                let mut values = vec![0.0; d + 1];
+                values[0] = 1.0;
+                for j in 1..=d {
+                    left[j] = u - knots[span + 1 - j];
+                    right[j] = knots[span + j] - u;
+                    let mut saved = 0.0;
+                    for r in 0..j {
+                        let denom = right[r + 1] + left[j - r];
+                        let temp = if denom != 0.0 { values[r] / denom } else { 0.0 };
+                        values[r] = saved + right[r + 1] * temp;
+                        saved = left[j - r] * temp;
+                    }
+                    values[j] = saved;
+                }
+                out.extend(values.into_iter().enumerate().map(|(i, v)| (span - d + i, v)));
+            }
+        }
+    }
+}
+
+/// Coefficients of the Bernstein polynomials of the given degree in the power basis.
+fn bezier_matrix(degree: usize) -> Vec<f32> {
+    let binomial = |n: usize, k: usize| (0..k).fold(1.0, |acc, i| acc * (n - i) as f32 / (i + 1) as f32);
+    let mut matrix = vec![0.0; (degree + 1) * (degree + 1)];
+    for i in 0..=degree {
+        for j in i..=degree {
+            let sign = if (j - i) % 2 == 0 { 1.0 } else { -1.0 };
+            matrix[i * (degree + 1) + j] = sign * binomial(degree, i) * binomial(degree - i, j - i);
+        }
+    }
+    matrix
+}
+
+/// A grid of vertices sampled from a surface.
+struct Patch {
+    /// Parameter values of the grid vertices.
+    params: Vec<[f32; 2]>,
+    /// Number of vertices along the u direction.
+    width: usize,
+    position: Vec<[f32; 3]>,
+    color: Option<Vec<[f32; 3]>>,
+    texture: Option<Vec<[f32; 3]>>,
+    normal: Vec<[f32; 3]>,
+}
+
+impl ObjData {
+    /// Tessellate all free-form surfaces into triangles.
+    ///
+    /// The generated vertices are appended to `position`, `texture` and `normal`, and the returned
+    /// group with the given name holds one triangle per polygon. Bezier, B-spline, cardinal, Taylor
+    /// and basis matrix surfaces are supported, including rational ones whose weights are taken
+    /// from `position_w`.
+    ///
+    /// The number of samples follows the `stech` technique of each surface, and the trimming
+    /// loops are sampled according to the `ctech` technique of their curves. Triangles whose
+    /// centers lie outside the `trim` loops or inside a `hole` loop are dropped.
+    ///
+    /// If any surface fails to tessellate or more than 2^24 vertices would be generated, an error
+    /// is returned and no vertices are appended.
+    pub fn tessellate_surfaces(&mut self, name: String) -> Result<Group, ObjError> {
+        let mut patches = Vec::with_capacity(self.free_form.surfaces.len());
+        let mut vertices = 0;
+        for idx in 0..self.free_form.surfaces.len() {
+            let surface = &self.free_form.surfaces[idx];
+            let failure = |message: String| ObjError::TessellationFailure { surface: idx, message };
+            let patch = self.sample_surface(surface, MAX_VERTICES - vertices).map_err(failure)?;
+            vertices += patch.position.len();
+            let trims = self.trim_loops(&surface.body.trims).map_err(failure)?;
+            let holes = self.trim_loops(&surface.body.holes).map_err(failure)?;
+
+            let keep = |corners: [usize; 3]| {
+                let mut center = [0.0; 2];
+                for &c in &corners {
+                    center[0] += patch.params[c][0] / 3.0;
+                    center[1] += patch.params[c][1] / 3.0;
+                }
+                (trims.is_empty() || trims.iter().any(|l| contains(l, center)))
+                    && !holes.iter().any(|l| contains(l, center))
+            };
+
+            let mut triangles = Vec::new();
+            let height = patch.params.len() / patch.width;
+            for j in 0..height - 1 {
+                for i in 0..patch.width - 1 {
+                    let a = j * patch.width + i;
+                    let (b, c, d) = (a + 1, a + patch.width + 1, a + patch.width);
+                    triangles.extend([[a, b, c], [a, c, d]].iter().copied().filter(|&t| keep(t)));
+                }
+            }
+            patches.push((patch, triangles));
+        }
+
+        let mut group = Group::new(name);
+        for (patch, triangles) in patches {
+            group.polys.extend(self.append_patch(&patch, triangles));
+        }
+        Ok(group)
+    }
+
+    /// Append the vertices of `patch` used by `triangles` and return the resulting polygons.
+    fn append_patch(&mut self, patch: &Patch, triangles: Vec<[usize; 3]>) -> Vec<SimplePolygon> {
+        let mut indices = vec![None; patch.position.len()];
+        let mut polys = Vec::with_capacity(triangles.len());
+        for triangle in triangles {
+            let mut poly = Vec::with_capacity(3);
+            for k in triangle {
+                let tuple = *indices[k].get_or_insert_with(|| {
+                    if !self.position_w.is_empty() {
+                        self.position_w.push(1.0);
+                    }
+                    if let Some(ref color) = patch.color {
+                        self.color.push(color[k]);
+                    }
+                    self.position.push(patch.position[k]);
+                    let texture = patch.texture.as_ref().map(|texture| {
+                        if !self.texture_w.is_empty() {
+                            self.texture_w.push(texture[k][2]);
+                        }
+                        self.texture.push([texture[k][0], texture[k][1]]);
+                        self.texture.len() - 1
+                    });
+                    self.normal.push(patch.normal[k]);
+                    IndexTuple(self.position.len() - 1, texture, Some(self.normal.len() - 1))
+                });
+                poly.push(tuple);
+            }
+            polys.push(SimplePolygon(poly));
+        }
+        polys
+    }
+
+    /// Sample the grid of vertices of a surface, failing if it has more than `max_vertices`.
+    fn sample_surface(&self, surface: &Surface, max_vertices: usize) -> Result<Patch, String> {
+        let attributes = &surface.attributes;
+        let (degree_u, degree_v) = match attributes.degree {
+            Some((u, v)) => (u, v.unwrap_or(u)),
+            None => return Err("missing `deg`".to_string()),
+        };
+        let (step_u, step_v) = match attributes.step {
+            Some((u, v)) => (Some(u), Some(v.unwrap_or(u))),
+            None => (None, None),
+        };
+        let vertices = surface.vertices.len();
+        let basis_u = Basis::new(
+            attributes,
+            degree_u,
+            &attributes.basis_u,
+            step_u,
+            &surface.body.parm_u,
+            vertices,
+        )?;
+        let basis_v = Basis::new(
+            attributes,
+            degree_v,
+            &attributes.basis_v,
+            step_v,
+            &surface.body.parm_v,
+            vertices,
+        )?;
+        if basis_u.count.checked_mul(basis_v.count) != Some(vertices) {
+            return Err(format!(
+                "expected {}x{} control vertices but found {}",
+                basis_u.count,
+                basis_v.count,
+                surface.vertices.len()
+            ));
+        }
+
+        let control = surface
+            .vertices
+            .iter()
+            .map(|&IndexTuple(p, _, _)| self.position.get(p).copied())
+            .collect::<Option<Vec<_>>>()
+            .ok_or("control vertex index out of range")?;
+        let weights: Vec<f32> = surface
+            .vertices
+            .iter()
+            .map(|v| match attributes.rational {
+                true => self.position_w.get(v.0).copied().unwrap_or(1.0),
+                false => 1.0,
+            })
+            .collect();
+        let colors: Option<Vec<[f32; 3]>> = match self.color.is_empty() {
+            true => None,
+            false => surface.vertices.iter().map(|v| self.color.get(v.0).copied()).collect(),
+        };
+        let textures: Option<Vec<[f32; 3]>> = surface
+            .vertices
+            .iter()
+            .map(|v| {
+                let t = v.1?;
+                let uv = self.texture.get(t)?;
+                Some([uv[0], uv[1], self.texture_w.get(t).copied().unwrap_or(0.0)])
+            })
+            .collect();
+        let normals: Option<Vec<[f32; 3]>> = surface
+            .vertices
+            .iter()
+            .map(|v| self.normal.get(v.2?).copied())
+            .collect();
+
+        let rows: Vec<Vec<[f32; 3]>> = control.chunks(basis_u.count).map(|row| row.to_vec()).collect();
+        let columns: Vec<Vec<[f32; 3]>> = (0..basis_u.count)
+            .map(|i| rows.iter().map(|row| row[i]).collect())
+            .collect();
+        let res_u = Approximation::resolution(
+            Approximation::from_surface(attributes.surface_technique, 0),
+            basis_u.spans(),
+            &rows,
+        );
+        let res_v = Approximation::resolution(
+            Approximation::from_surface(attributes.surface_technique, 1),
+            basis_v.spans(),
+            &columns,
+        );
+        let us = basis_u.samples(surface.range_u, res_u);
+        let vs = basis_v.samples(surface.range_v, res_v);
+        if us.len().saturating_mul(vs.len()) > max_vertices {
+            return Err(format!("more than {} vertices in total", MAX_VERTICES));
+        }
+
+        let mut patch = Patch {
+            params: Vec::with_capacity(us.len() * vs.len()),
+            width: us.len(),
+            position: Vec::with_capacity(us.len() * vs.len()),
+            color: colors.as_ref().map(|_| Vec::new()),
+            texture: textures.as_ref().map(|_| Vec::new()),
+            normal: Vec::with_capacity(us.len() * vs.len()),
+        };
+        let (mut values_u, mut values_v) = (Vec::new(), Vec::new());
+        let mut combined = Vec::new();
+        for &v in &vs {
+            basis_v.eval(v, &mut values_v);
+            for &u in &us {
+                basis_u.eval(u, &mut values_u);
+                combined.clear();
+                let mut total = 0.0;
+                for &(j, bv) in &values_v {
+                    for &(i, bu) in &values_u {
+                        let k = j * basis_u.count + i;
+                        let value = bu * bv * weights[k];
+                        total += value;
+                        combined.push((k, value));
+                    }
+                }
+                let blend = |points: &[[f32; 3]]| {
+                    let mut sum = [0.0; 3];
+                    for &(k, value) in &combined {
+                        for c in 0..3 {
+                            sum[c] += points[k][c] * value;
+                        }
+                    }
+                    if total != 0.0 {
+                        [sum[0] / total, sum[1] / total, sum[2] / total]
+                    } else {
+                        sum
+                    }
+                };
+
+                patch.params.push([u, v]);
+                patch.position.push(blend(&control));
+                if let (Some(out), Some(colors)) = (patch.color.as_mut(), colors.as_ref()) {
+                    out.push(blend(colors));
+                }
+                if let (Some(out), Some(textures)) = (patch.texture.as_mut(), textures.as_ref()) {
+                    out.push(blend(textures));
+                }
+                if let Some(ref normals) = normals {
+                    patch.normal.push(normalized(blend(normals)).unwrap_or([0.0, 0.0, 1.0]));
+                }
+            }
+        }
+
+        if normals.is_none() {
+            // Use the partial derivatives estimated from neighboring samples.
+            let (width, height) = (us.len(), vs.len());
+            for j in 0..height {
+                for i in 0..width {
+                    let at = |i: usize, j: usize| patch.position[j * width + i];
+                    let du = sub(at((i + 1).min(width - 1), j), at(i.saturating_sub(1), j));
+                    let dv = sub(at(i, (j + 1).min(height - 1)), at(i, j.saturating_sub(1)));
+                    patch.normal.push(normalized(cross(du, dv)).unwrap_or([0.0, 0.0, 1.0]));
+                }
+            }
+        }
+
+        Ok(patch)
+    }
+
+    /// Sample the given trimming loops into closed polygons in the parameter space of a surface.
+    fn trim_loops(&self, loops: &[Vec<CurveRef>]) -> Result<Vec<Vec<[f32; 2]>>, String> {
+        let mut polygons = Vec::with_capacity(loops.len());
+        for curves in loops {
+            let mut polygon = Vec::new();
+            for curve_ref in curves {
+                let curve = self
+                    .free_form
+                    .curves2
+                    .get(curve_ref.curve)
+                    .ok_or("trimming curve index out of range")?;
+                let attributes = &curve.attributes;
+                let degree = attributes.degree.ok_or("missing `deg`")?.0;
+                let basis = Basis::new(
+                    attributes,
+                    degree,
+                    &attributes.basis_u,
+                    attributes.step.map(|s| s.0),
+                    &curve.body.parm_u,
+                    curve.vertices.len(),
+                )?;
+                if basis.count != curve.vertices.len() {
+                    return Err(format!(
+                        "expected {} trimming curve control points but found {}",
+                        basis.count,
+                        curve.vertices.len()
+                    ));
+                }
+
+                let control = curve
+                    .vertices
+                    .iter()
+                    .map(|&vp| {
+                        let vp = self.parameter.get(vp)?;
+                        let w = if attributes.rational { vp.2.unwrap_or(1.0) } else { 1.0 };
+                        Some(([vp.0, vp.1.unwrap_or(0.0), 0.0], w))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("parameter vertex index out of range")?;
+                let points: Vec<[f32; 3]> = control.iter().map(|c| c.0).collect();
+                let res = Approximation::resolution(
+                    Approximation::from_curve(attributes.curve_technique),
+                    basis.spans(),
+                    &[points],
+                );
+
+                let mut values = Vec::new();
+                for u in basis.samples(curve_ref.range, res) {
+                    basis.eval(u, &mut values);
+                    let (mut sum, mut total) = ([0.0; 2], 0.0);
+                    for &(k, value) in &values {
+                        let (point, w) = control[k];
+                        sum[0] += point[0] * value * w;
+                        sum[1] += point[1] * value * w;
+                        total += value * w;
+                    }
+                    if total != 0.0 {
+                        polygon.push([sum[0] / total, sum[1] / total]);
+                    }
+                }
+            }
+            polygons.push(polygon);
+        }
+        Ok(polygons)
+    }
+}
@@ -0,0 +1,160 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Streaming of `.obj` files that are too large to be loaded into an `ObjData`.
+
+use std::io::Read;
+
+use crate::obj::{IndexKind, IndexTuple, ObjError};
+use crate::reader::{ObjReader, Statement};
+use crate::warning::Warning;
+
+/// A vertex of a face together with the data its indices refer to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaceVertex {
+    /// The 0-based indices of the vertex.
+    pub index: IndexTuple,
+    /// The position of the vertex.
+    pub position: [f32; 3],
+    /// The texture coordinates of the vertex, if it has any.
+    pub texture: Option<[f32; 2]>,
+    /// The normal of the vertex, if it has one.
+    pub normal: Option<[f32; 3]>,
+}
+
+/// Callbacks for the data of an `.obj` file as it is read by [`ObjReader::visit`].
+///
+/// All methods do nothing by default.
+pub trait Visitor {
+    /// A vertex position `v`.
+    fn vertex(&mut self, _position: [f32; 3]) {}
+    /// Texture coordinates `vt`, with a missing `v` component set to 0.
+    fn texture(&mut self, _texture: [f32; 2]) {}
+    /// A normal `vn`.
+    fn normal(&mut self, _normal: [f32; 3]) {}
+    /// A face `f` with the data of its vertices.
+    fn face(&mut self, _face: &[FaceVertex]) {}
+    /// An object name `o`.
+    fn object(&mut self, _name: &str) {}
+    /// The whitespace separated names of a group `g`.
+    fn group(&mut self, _names: &str) {}
+    /// A material `usemtl`.
+    fn material(&mut self, _name: Option<&str>) {}
+    /// A problem that was fixed up while reading.
+    fn warning(&mut self, _warning: Warning) {}
+}
+
+/// The vertex data kept while visiting to resolve the indices of faces.
+#[derive(Default)]
+struct Attributes {
+    position: Vec<[f32; 3]>,
+    texture: Vec<[f32; 2]>,
+    normal: Vec<[f32; 3]>,
+}
+
+impl Attributes {
+    fn get<T: Copy>(data: &[T], line_number: usize, kind: IndexKind, index: usize) -> Result<T, ObjError> {
+        data.get(index).copied().ok_or(ObjError::IndexOutOfRange {
+            line_number,
+            kind,
+            // Only forward references are left unchecked by the reader.
+            index: index as isize + 1,
+            len: data.len(),
+        })
+    }
+
+    fn vertex(&self, line_number: usize, index: IndexTuple) -> Result<FaceVertex, ObjError> {
+        let IndexTuple(p, t, n) = index;
+        Ok(FaceVertex {
+            index,
+            position: Self::get(&self.position, line_number, IndexKind::Position, p)?,
+            texture: t
+                .map(|t| Self::get(&self.texture, line_number, IndexKind::Texture, t))
+                .transpose()?,
+            normal: n
+                .map(|n| Self::get(&self.normal, line_number, IndexKind::Normal, n))
+                .transpose()?,
+        })
+    }
+}
+
+impl<R: Read> ObjReader<R> {
+    /// Pass the vertex data and the faces of the remaining input to the given visitor.
+    ///
+    /// Only positions, texture coordinates and normals are kept to look up the data of the face
+    /// vertices, while faces are dropped once they are visited. So even files that are too large
+    /// for [`ObjData`](crate::ObjData) can be processed.
+    ///
+    /// Faces must only reference vertex data declared before them, even if
+    /// [`LoadConfig::forward_references`](crate::LoadConfig::forward_references) is set. Unknown
+    /// commands are rejected in strict mode, while all other statements are skipped.
+    pub fn visit<V: Visitor>(mut self, visitor: &mut V) -> Result<(), ObjError> {
+        let strict = self.config.strict;
+        let mut attributes = Attributes::default();
+        let mut face = Vec::new();
+
+        while let Some((idx, statement)) = self.next_statement() {
+            let result = statement.and_then(|statement| {
+                match statement {
+                    Statement::Vertex { position, .. } => {
+                        attributes.position.push(position);
+                        visitor.vertex(position);
+                    }
+                    Statement::TexCoord { uvw, .. } => {
+                        let texture = [uvw[0], uvw[1]];
+                        attributes.texture.push(texture);
+                        visitor.texture(texture);
+                    }
+                    Statement::Normal(normal) => {
+                        attributes.normal.push(normal);
+                        visitor.normal(normal);
+                    }
+                    Statement::Face(indices) => {
+                        face.clear();
+                        for &index in indices {
+                            face.push(attributes.vertex(idx, index)?);
+                        }
+                        visitor.face(&face);
+                    }
+                    Statement::Object(name) => visitor.object(name),
+                    Statement::Group(names) => visitor.group(names),
+                    Statement::UseMtl(name) => visitor.material(name),
+                    Statement::Unknown { keyword, .. } if strict => {
+                        return Err(ObjError::UnexpectedCommand {
+                            line_number: idx,
+                            command: keyword.to_string(),
+                        });
+                    }
+                    Statement::Unknown { keyword, .. } => visitor.warning(Warning::UnknownCommand {
+                        line_number: idx,
+                        command: keyword.to_string(),
+                    }),
+                    _ => (),
+                }
+                Ok(())
+            });
+
+            for warning in self.warnings() {
+                visitor.warning(warning);
+            }
+            if let Err(err) = result {
+                return Err(match err {
+                    ObjError::Io(_) => err,
+                    err => err.located(None, idx, self.line()),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+//! Non-fatal problems found while loading `.obj` and `.mtl` files.
+
+use std::fmt;
+
+/// A problem that the loaders fixed up instead of failing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A zero texture index was dropped from a vertex of an element.
+    ZeroTextureIndex { line_number: usize },
+    /// A zero normal index was dropped from a vertex of an element.
+    ZeroNormalIndex { line_number: usize },
+    /// A command that is not in the spec was skipped because strict mode is disabled.
+    UnknownCommand { line_number: usize, command: String },
+    /// Arguments after those of a valid `v` statement were ignored because strict mode is disabled.
+    ExtraVertexArguments { line_number: usize, args: String },
+    /// An `s` statement without a valid smoothing group number turned smoothing off.
+    MalformedSmoothingGroup { line_number: usize, args: String },
+    /// A `.mtl` statement before the first `newmtl` was skipped.
+    StatementOutsideMaterial { line_number: usize, command: String },
+    /// A material named by `usemtl` wasn't found in any of the loaded material libraries.
+    ///
+    /// The material of the group is left as [`ObjMaterial::Ref`](crate::ObjMaterial::Ref).
+    UnresolvedMaterial {
+        object: String,
+        group: String,
+        material: String,
+    },
+    /// A warning in a file named by a `call` statement.
+    Called {
+        line_number: usize,
+        filename: String,
+        warning: Box<Warning>,
+    },
+    /// A warning in a material library.
+    MaterialLibrary { filename: String, warning: Box<Warning> },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::ZeroTextureIndex { line_number } => {
+                write!(f, "Zero texture index was ignored. (line: {})", line_number)
+            }
+            Warning::ZeroNormalIndex { line_number } => {
+                write!(f, "Zero normal index was ignored. (line: {})", line_number)
+            }
+            Warning::UnknownCommand { line_number, command } => write!(
+                f,
+                "Command that is not in the spec was ignored. (line: {}, command: {})",
+                line_number, command
+            ),
+            Warning::ExtraVertexArguments { line_number, args } => write!(
+                f,
+                "Extra vertex arguments were ignored. (line: {}, arguments: {})",
+                line_number, args
+            ),
+            Warning::MalformedSmoothingGroup { line_number, args } => write!(
+                f,
+                "Malformed smoothing group turned smoothing off. (line: {}, arguments: {})",
+                line_number, args
+            ),
+            Warning::StatementOutsideMaterial { line_number, command } => write!(
+                f,
+                "Statement before the first newmtl was ignored. (line: {}, command: {})",
+                line_number, command
+            ),
+            Warning::UnresolvedMaterial {
+                object,
+                group,
+                material,
+            } => write!(
+                f,
+                "Material was not found in the material libraries. (object: {}, group: {}, material: {})",
+                object, group, material
+            ),
+            Warning::Called {
+                line_number,
+                filename,
+                warning,
+            } => write!(f, "{} (called on line: {}, file: {})", warning, line_number, filename),
+            Warning::MaterialLibrary { filename, warning } => write!(f, "{} (material library: {})", warning, filename),
+        }
+    }
+}
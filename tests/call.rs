@@ -0,0 +1,254 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{Call, Error, IncludePolicy, IndexTuple, LoadConfig, Obj, ObjData, ObjError, ShellCommand, SimplePolygon};
+use std::{fs, io};
+
+static MAIN: &str = "
+v 0 0 0
+v 1 0 0
+v 1 1 0
+g base
+f 1 2 3
+call triangle.obj 2
+csh -echo done
+f 1 2 3
+";
+
+static TRIANGLE: &str = "
+v 0 0 $1
+v 1 0 $1
+v 1 1 $1
+g top
+f 1 2 -1
+";
+
+fn resolve(filename: &str) -> io::Result<&'static [u8]> {
+    match filename {
+        "triangle.obj" => Ok(TRIANGLE.as_bytes()),
+        "cycle.obj" => Ok(b"call cycle.obj"),
+        _ => Err(io::Error::new(io::ErrorKind::NotFound, filename.to_string())),
+    }
+}
+
+fn config(include: IncludePolicy) -> LoadConfig {
+    LoadConfig {
+        include,
+        ..LoadConfig::default()
+    }
+}
+
+fn triangle(a: usize) -> SimplePolygon {
+    SimplePolygon((a..a + 3).map(|i| IndexTuple(i, None, None)).collect())
+}
+
+#[test]
+fn record_calls() {
+    let obj = ObjData::load_buf(MAIN.as_bytes()).unwrap();
+
+    assert_eq!(
+        obj.calls,
+        vec![Call {
+            filename: "triangle.obj".to_string(),
+            args: vec!["2".to_string()],
+        }]
+    );
+    assert_eq!(
+        obj.shell_commands,
+        vec![ShellCommand {
+            command: "echo done".to_string(),
+            ignore_errors: true,
+        }]
+    );
+    assert_eq!(obj.position.len(), 3);
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("call triangle.obj 2\ncsh -echo done\n"));
+    assert_eq!(ObjData::load_buf(output.as_bytes()).unwrap(), obj);
+}
+
+#[test]
+fn ignore_calls() {
+    let obj = ObjData::load_buf_with_config(MAIN.as_bytes(), config(IncludePolicy::Ignore)).unwrap();
+
+    assert!(obj.calls.is_empty());
+    assert!(obj.shell_commands.is_empty());
+}
+
+#[test]
+fn resolve_calls() {
+    let obj = ObjData::load_buf_with_resolver(MAIN.as_bytes(), config(IncludePolicy::Resolve), resolve).unwrap();
+
+    assert!(obj.calls.is_empty());
+    assert_eq!(obj.shell_commands.len(), 1);
+    assert_eq!(obj.position.len(), 6);
+    assert_eq!(obj.position[3], [0., 0., 2.]);
+
+    let groups: Vec<_> = obj.objects[0]
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.polys.clone()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![("base", vec![triangle(0)]), ("top", vec![triangle(3), triangle(0)])]
+    );
+}
+
+#[test]
+fn resolve_missing_call() {
+    let err = ObjData::load_buf_with_config(MAIN.as_bytes(), config(IncludePolicy::Resolve)).unwrap_err();
+    match err.kind() {
+        ObjError::IncludeFailure {
+            line_number: 7,
+            filename,
+            error,
+        } => {
+            assert_eq!(filename, "triangle.obj");
+            assert!(matches!(**error, ObjError::Io(_)));
+        }
+        other => panic!("expected an include failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolve_call_cycle() {
+    let err = ObjData::load_buf_with_resolver("call cycle.obj".as_bytes(), config(IncludePolicy::Resolve), resolve)
+        .unwrap_err();
+    match err.kind() {
+        ObjError::IncludeFailure {
+            line_number: 1, error, ..
+        } => match error.kind() {
+            ObjError::IncludeCycle {
+                line_number: 1,
+                filename,
+            } => assert_eq!(filename, "cycle.obj"),
+            other => panic!("expected an include cycle, got {:?}", other),
+        },
+        other => panic!("expected an include failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolve_aliased_call_cycle() {
+    let resolve = |filename: &str| -> io::Result<&'static [u8]> {
+        match filename {
+            "./a.obj" => Ok(b"call ././a.obj"),
+            "././a.obj" => Ok(b"call a.obj"),
+            _ => Ok(b"call ./a.obj"),
+        }
+    };
+    let err =
+        ObjData::load_buf_with_resolver("call a.obj".as_bytes(), config(IncludePolicy::Resolve), resolve).unwrap_err();
+    match err.kind() {
+        ObjError::IncludeFailure { error, .. } => match error.kind() {
+            ObjError::IncludeCycle { filename, .. } => assert_eq!(filename, "./a.obj"),
+            other => panic!("expected an include cycle, got {:?}", other),
+        },
+        other => panic!("expected an include failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolve_aliased_self_call_from_file() {
+    let dir = std::env::temp_dir().join("obj_resolve_aliased_self_call");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("a.obj");
+    fs::write(&path, "v 0 0 0\ncall ./a.obj\n").unwrap();
+
+    let err = Obj::load_with_config(&path, config(IncludePolicy::Resolve)).unwrap_err();
+    match err {
+        Error::Obj { error, .. } => match error.kind() {
+            ObjError::IncludeCycle {
+                line_number: 2,
+                filename,
+            } => assert_eq!(filename, "./a.obj"),
+            other => panic!("expected an include cycle, got {:?}", other),
+        },
+        other => panic!("expected an obj error, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolve_deeply_nested_calls() {
+    let resolve = |filename: &str| -> io::Result<io::Cursor<String>> {
+        let depth: usize = filename.trim_end_matches(".obj").parse().unwrap();
+        Ok(io::Cursor::new(format!("call {}.obj", depth + 1)))
+    };
+    let err =
+        ObjData::load_buf_with_resolver("call 1.obj".as_bytes(), config(IncludePolicy::Resolve), resolve).unwrap_err();
+    let mut err = &err;
+    let mut depth = 0;
+    while let ObjError::IncludeFailure { error, .. } = err.kind() {
+        err = error;
+        depth += 1;
+    }
+    assert_eq!(depth, 32);
+    assert!(matches!(err.kind(), ObjError::IncludeTooDeep { filename, .. } if filename == "33.obj"));
+}
+
+#[test]
+fn resolve_nested_calls_relative_to_caller() {
+    let resolve = |filename: &str| -> io::Result<&'static [u8]> {
+        match filename {
+            "parts/wheel.obj" => Ok(b"call hub.obj\nv 0 0 1\n"),
+            "parts/hub.obj" => Ok(b"v 0 0 2\n"),
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, filename.to_string())),
+        }
+    };
+    let obj = ObjData::load_buf_with_resolver(
+        "call parts/wheel.obj".as_bytes(),
+        config(IncludePolicy::Resolve),
+        resolve,
+    )
+    .unwrap();
+    assert_eq!(obj.position, vec![[0., 0., 2.], [0., 0., 1.]]);
+
+    let dir = std::env::temp_dir().join("obj_resolve_nested_calls_relative_to_caller");
+    fs::create_dir_all(dir.join("parts")).unwrap();
+    fs::write(dir.join("parts/wheel.obj"), "call hub.obj\nv 0 0 1\n").unwrap();
+    fs::write(dir.join("parts/hub.obj"), "v 0 0 2\n").unwrap();
+    let path = dir.join("car.obj");
+    fs::write(&path, "call parts/wheel.obj\n").unwrap();
+
+    let obj = Obj::load_with_config(&path, config(IncludePolicy::Resolve)).unwrap();
+    assert_eq!(obj.data.position, vec![[0., 0., 2.], [0., 0., 1.]]);
+}
+
+#[test]
+fn resolve_call_keeps_called_header() {
+    let resolve = |_: &str| -> io::Result<&'static [u8]> { Ok(b"# triangle\nv 0 0 0\ng top\nf 1 1 1\n") };
+    let config = LoadConfig {
+        preserve_lines: true,
+        ..config(IncludePolicy::Resolve)
+    };
+    let obj = ObjData::load_buf_with_resolver(
+        "# main\nv 0 0 0\ng base\nf 1 1 1\ncall triangle.obj\n".as_bytes(),
+        config,
+        resolve,
+    )
+    .unwrap();
+    assert_eq!(obj.header, Some(vec!["# main".to_string()]));
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("g base\nf 1 1 1\n# triangle\ng top\nf 2 2 2\n"),
+        "{}",
+        output
+    );
+}
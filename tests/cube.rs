@@ -19,7 +19,7 @@ use obj::IndexTuple;
 use obj::ObjData;
 use std::io::BufReader;
 
-static SQUARE: &'static str = "
+static SQUARE: &str = "
 v 0 1 0
 v 0 0 0
 v 1 0 0
@@ -27,7 +27,7 @@ v 1 1 0
 f 1 2 3 4
 ";
 
-static SQUARE_VBO: &'static [[f32; 3]] = &[[0., 1., 0.], [0., 0., 0.], [1., 0., 0.], [1., 1., 0.]];
+static SQUARE_VBO: &[[f32; 3]] = &[[0., 1., 0.], [0., 0., 0.], [1., 0., 0.], [1., 1., 0.]];
 
 #[test]
 #[cfg(feature = "genmesh")]
@@ -73,7 +73,7 @@ fn test_load_square_nodeps() {
     }
 }
 
-static CUBE: &'static str = "
+static CUBE: &str = "
 v 0 1 1
 v 0 0 1
 v 1 0 1
@@ -100,7 +100,7 @@ f 2 6 7 3
 # 6 elements
 ";
 
-static CUBE_VBO: &'static [[f32; 3]] = &[
+static CUBE_VBO: &[[f32; 3]] = &[
     [0., 1., 1.],
     [0., 0., 1.],
     [1., 0., 1.],
@@ -111,14 +111,7 @@ static CUBE_VBO: &'static [[f32; 3]] = &[
     [1., 1., 0.],
 ];
 
-static CUBE_NAMES: &'static [&'static str] = &[
-    "front cube",
-    "back cube",
-    "right cube",
-    "top cube",
-    "left cube",
-    "bottom cube",
-];
+static CUBE_NAMES: &[&str] = &["front", "back", "right", "top", "left", "bottom"];
 
 #[test]
 #[cfg(feature = "genmesh")]
@@ -136,6 +129,7 @@ fn test_load_cube() {
         assert_eq!(obj.name, "cube");
         for (g, &name) in obj.groups.iter().zip(CUBE_NAMES.iter()) {
             assert_eq!(name, g.name);
+            assert_eq!(g.additional_names, vec!["cube"]);
         }
     }
 }
@@ -155,11 +149,12 @@ fn test_load_cube_nodeps() {
         assert_eq!(obj.name, "cube");
         for (g, &name) in obj.groups.iter().zip(CUBE_NAMES.iter()) {
             assert_eq!(name, g.name);
+            assert_eq!(g.additional_names, vec!["cube"]);
         }
     }
 }
 
-static CUBE_NEGATIVE_VBO: &'static [[f32; 3]] = &[
+static CUBE_NEGATIVE_VBO: &[[f32; 3]] = &[
     [0., 1., 1.],
     [0., 0., 1.],
     [1., 0., 1.],
@@ -186,7 +181,7 @@ static CUBE_NEGATIVE_VBO: &'static [[f32; 3]] = &[
     [1., 0., 1.],
 ];
 
-static CUBE_NEGATIVE: &'static str = "
+static CUBE_NEGATIVE: &str = "
 v 0 1 1
 v 0 0 1
 v 1 0 1
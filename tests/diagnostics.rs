@@ -0,0 +1,97 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{Error, LoadConfig, Mtl, MtlError, Obj, ObjData, ObjError};
+
+#[test]
+fn locate_bad_token() {
+    let err = ObjData::load_buf("v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 0 2\n".as_bytes()).unwrap_err();
+    let location = err.location().unwrap();
+
+    assert!(matches!(err.kind(), ObjError::ZeroVertexNumber { line_number: 4 }));
+    assert_eq!(location.path, None);
+    assert_eq!(location.line_number, 4);
+    assert_eq!(location.span, 4..5);
+    assert_eq!(location.column(), 5);
+    assert_eq!(location.line, "f 1 0 2");
+    assert_eq!(
+        err.render(),
+        "error: Zero vertex numbers are invalid. (line: 4)\n \
+         --> 4:5\n  \
+         |\n\
+         4 | f 1 0 2\n  \
+         |     ^"
+    );
+}
+
+#[test]
+fn locate_statement_parts() {
+    let span = |input: &str| {
+        let err = ObjData::load_buf(input.as_bytes()).unwrap_err();
+        let location = err.location().unwrap();
+        location.line[location.span.clone()].to_string()
+    };
+
+    assert_eq!(span("v 0 0 0\nf 1 1/x z\n"), "z");
+    assert_eq!(span("v 0 0 0\nvt 0 0\nf 1/1 1/2 1\n"), "1/2");
+    assert_eq!(span("v 0 0 0\nvn 0 0 1\nf 1 1 1//2\n"), "1//2");
+    assert_eq!(span("\tfoo bar\n"), "foo");
+    assert_eq!(span("v 1 x 3\n"), "1 x 3");
+    assert_eq!(span("v 0 0 0\nl 1 1/1/1\n"), "1/1/1");
+
+    // Missing arguments are pointed at the end of the statement.
+    let err = ObjData::load_buf("mtllib".as_bytes()).unwrap_err();
+    assert_eq!(err.location().unwrap().span, 6..6);
+    assert!(err.render().ends_with("1 | mtllib\n  |       ^"));
+
+    // Tabs are kept in front of the carets.
+    let err = ObjData::load_buf("\tfoo bar\n".as_bytes()).unwrap_err();
+    assert!(err.render().ends_with("1 | \tfoo bar\n  | \t^^^"));
+}
+
+#[test]
+fn locate_in_file() {
+    let dir = std::env::temp_dir().join("obj_locate_in_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("broken.obj");
+    std::fs::write(&path, "# broken\nv 0 0 0\nf 1 -2 1\n").unwrap();
+
+    let err = match Obj::load_with_config(&path, LoadConfig::default()) {
+        Err(Error::Obj { error, .. }) => error,
+        other => panic!("expected an obj error, got {:?}", other),
+    };
+    let location = err.location().unwrap();
+    assert_eq!(location.path.as_deref(), Some(path.as_path()));
+    assert_eq!(location.line_number, 3);
+    assert_eq!(location.span, 4..6);
+    assert!(err.to_string().starts_with(&format!("{}:3:5: ", path.display())));
+}
+
+#[test]
+fn locate_mtl_error() {
+    let mut mtl = Mtl::new("broken.mtl".to_string());
+    let err = mtl.reload("newmtl red\nNs 1\nKd 1 0 x\n".as_bytes()).unwrap_err();
+    let location = err.location().unwrap();
+
+    assert!(matches!(err.kind(), MtlError::InvalidValue(_)));
+    assert_eq!(location.line_number, 3);
+    assert_eq!(&location.line[location.span.clone()], "1 0 x");
+    assert_eq!(
+        err.render(),
+        format!("error: {}\n --> 3:4\n  |\n3 | Kd 1 0 x\n  |    ^^^^^", err.kind())
+    );
+
+    let err = mtl.reload("newmtl red\nmap_Kd\n".as_bytes()).unwrap_err();
+    assert_eq!(err.location().unwrap().span, 6..6);
+}
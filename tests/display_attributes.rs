@@ -0,0 +1,98 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{DisplayAttributes, ObjData, ObjError};
+
+fn display(obj: &ObjData) -> Vec<(&str, usize, &DisplayAttributes)> {
+    obj.objects[0]
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.index, &g.display))
+        .collect()
+}
+
+#[test]
+fn display_attribute_changes_split_groups() {
+    let input = "v 0 0 0\ng quads\nbevel on\nc_interp off\nf 1 1 1\nlod 2\nd_interp on\nf 1 1 1\ng other\nf 1 1 1\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    let base = DisplayAttributes {
+        bevel: Some(true),
+        color_interpolation: Some(false),
+        ..DisplayAttributes::default()
+    };
+    let detailed = DisplayAttributes {
+        lod: Some(2),
+        dissolve_interpolation: Some(true),
+        ..base.clone()
+    };
+    assert_eq!(
+        display(&obj),
+        vec![("quads", 0, &base), ("quads", 1, &detailed), ("other", 0, &detailed)]
+    );
+}
+
+#[test]
+fn usemap_off() {
+    let input = "v 0 0 0\ng plain\nf 1 1 1\ng wooden\nusemap wood\nf 1 1 1\ng unmapped\nusemap off\nf 1 1 1\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    // A group without `usemap` differs from one whose map was turned off.
+    let maps: Vec<_> = obj.objects[0]
+        .groups
+        .iter()
+        .map(|g| g.display.texture_map.clone())
+        .collect();
+    assert_eq!(maps, vec![None, Some(Some("wood".to_string())), Some(None)]);
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("g unmapped\nusemap off\nf 1 1 1\n"), "{}", output);
+    assert_eq!(ObjData::load_buf(output.as_bytes()).unwrap(), obj);
+}
+
+#[test]
+fn render_statements() {
+    let input = "maplib base.mpc detail.mpc\nshadow_obj shadow.obj\ntrace_obj trace.obj\nv 0 0 0\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    assert_eq!(obj.map_libs, vec!["base.mpc", "detail.mpc"]);
+    assert_eq!(obj.shadow_obj.as_deref(), Some("shadow.obj"));
+    assert_eq!(obj.trace_obj.as_deref(), Some("trace.obj"));
+
+    // Each map library is written on its own line.
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("maplib base.mpc\nmaplib detail.mpc\nshadow_obj shadow.obj\ntrace_obj trace.obj\n"));
+}
+
+#[test]
+fn invalid_display_attributes() {
+    for (input, list) in &[
+        ("bevel yes", "yes"),
+        ("lod", ""),
+        ("lod 1 2", "1 2"),
+        ("shadow_obj", ""),
+    ] {
+        match ObjData::load_buf(input.as_bytes()).unwrap_err().kind() {
+            ObjError::ArgumentListFailure {
+                line_number: 1,
+                list: l,
+            } => assert_eq!(l, list),
+            other => panic!("expected an argument list failure for {:?}, got {:?}", input, other),
+        }
+    }
+}
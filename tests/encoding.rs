@@ -0,0 +1,158 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{LoadConfig, NameEncoding, Obj, ObjData, ObjMaterial};
+
+// "Façade" and "Fenêtre" in Latin-1, as written by many legacy exporters.
+static LATIN1_SQUARE: &[u8] = b"# Export fran\xe7ais
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+g Fa\xe7ade
+usemtl Fen\xeatre
+f 1 2 3 4
+";
+
+fn load(encoding: NameEncoding) -> ObjData {
+    let config = LoadConfig {
+        encoding,
+        ..LoadConfig::default()
+    };
+    ObjData::load_buf_with_config(LATIN1_SQUARE, config).unwrap()
+}
+
+#[test]
+fn lossy_utf8() {
+    let obj = load(NameEncoding::Utf8Lossy);
+    let group = &obj.objects[0].groups[0];
+    assert_eq!(group.name, "Fa\u{fffd}ade");
+    assert_eq!(group.polys.len(), 1);
+    assert_eq!(obj.position.len(), 4);
+}
+
+#[test]
+fn latin1() {
+    let obj = load(NameEncoding::Latin1);
+    let group = &obj.objects[0].groups[0];
+    assert_eq!(group.name, "Façade");
+    assert_eq!(group.material, Some(ObjMaterial::Ref("Fenêtre".to_string())));
+    assert_eq!(NameEncoding::Latin1.encode(&group.name).as_ref(), b"Fa\xe7ade");
+}
+
+#[test]
+fn raw_bytes() {
+    let obj = load(NameEncoding::Raw);
+    let group = &obj.objects[0].groups[0];
+    assert_eq!(group.name, "Fa\u{efe7}ade");
+    assert_eq!(NameEncoding::Raw.encode(&group.name).as_ref(), b"Fa\xe7ade");
+
+    // Valid UTF-8 is decoded as usual.
+    let obj = ObjData::load_buf_with_config(
+        "g Façade\n".as_bytes(),
+        LoadConfig {
+            encoding: NameEncoding::Raw,
+            ..LoadConfig::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(obj.objects[0].groups[0].name, "Façade");
+}
+
+#[test]
+fn material_library_encoding() {
+    let obj = ObjData::load_buf_with_config(
+        b"mtllib fen\xeatres.mtl\n".as_ref(),
+        LoadConfig {
+            encoding: NameEncoding::Latin1,
+            ..LoadConfig::default()
+        },
+    )
+    .unwrap();
+    let mut mtl = obj.material_libs[0].clone();
+    assert_eq!(mtl.filename, "fenêtres.mtl");
+    assert_eq!(mtl.encoding, NameEncoding::Latin1);
+
+    mtl.reload(b"newmtl Fen\xeatre\nKd 1 1 1\n".as_ref()).unwrap();
+    assert_eq!(mtl.materials[0].name, "Fenêtre");
+}
+
+#[test]
+fn invalid_utf8_in_comment() {
+    let obj = ObjData::load_buf(b"# \xff\xfe\nv 0 0 0\np 1\n".as_ref()).unwrap();
+    assert_eq!(obj.position.len(), 1);
+}
+
+#[test]
+fn round_trip_encodings() {
+    for &encoding in &[NameEncoding::Latin1, NameEncoding::Raw] {
+        let obj = load(encoding);
+        let mut output = Vec::new();
+        obj.write_to_buf(&mut output).unwrap();
+        let group: &[u8] = b"g Fa\xe7ade\n";
+        assert!(output.windows(group.len()).any(|line| line == group));
+
+        let config = LoadConfig {
+            encoding,
+            ..LoadConfig::default()
+        };
+        assert_eq!(ObjData::load_buf_with_config(&output[..], config).unwrap(), obj);
+    }
+}
+
+#[test]
+fn raw_private_use_characters() {
+    // An invalid byte next to the character that would hold it and the UTF-8 bytes of that character.
+    let input: &[u8] = b"g a\xe7\xee\xbf\xa7\n";
+    let obj = ObjData::load_buf_with_config(
+        input,
+        LoadConfig {
+            encoding: NameEncoding::Raw,
+            ..LoadConfig::default()
+        },
+    )
+    .unwrap();
+    let name = &obj.objects[0].groups[0].name;
+    assert_eq!(name, "a\u{efe7}\u{efee}\u{efbf}\u{efa7}");
+    assert_eq!(NameEncoding::Raw.encode(name).as_ref(), b"a\xe7\xee\xbf\xa7");
+}
+
+#[test]
+#[cfg(unix)]
+fn load_material_library_with_encoded_name() {
+    use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt};
+
+    let dir = std::env::temp_dir().join("obj_load_material_library_with_encoded_name");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join(OsStr::from_bytes(b"fen\xeatres.mtl")),
+        b"newmtl Fen\xeatre\nKd 1 1 1\n".as_ref(),
+    )
+    .unwrap();
+    let path = dir.join("square.obj");
+    fs::write(&path, [LATIN1_SQUARE, b"mtllib fen\xeatres.mtl\n"].concat()).unwrap();
+
+    for &encoding in &[NameEncoding::Latin1, NameEncoding::Raw] {
+        let config = LoadConfig {
+            encoding,
+            ..LoadConfig::default()
+        };
+        let mut obj = Obj::load_with_config(&path, config).unwrap();
+        obj.load_mtls().unwrap();
+        match obj.data.objects[0].groups[0].material {
+            Some(ObjMaterial::Mtl(ref material)) => assert_eq!(material.kd, Some([1.0, 1.0, 1.0])),
+            ref other => panic!("expected a loaded material, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{Error, MtlError, Obj, ObjError, Operation};
+use std::error::Error as _;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn load_missing_file() {
+    let path = temp_dir("obj_load_missing_file").join("missing.obj");
+    let err = Obj::load(&path).unwrap_err();
+
+    match err {
+        Error::Obj {
+            path: ref p,
+            operation: Operation::Load,
+            error: ObjError::Io(_),
+        } => assert_eq!(p, &path),
+        ref other => panic!("expected an obj load error, got {:?}", other),
+    }
+    assert_eq!(err.to_string(), format!("Failed to load {}", path.display()));
+
+    // The chain leads from the file to the underlying I/O error.
+    let source = err.source().unwrap();
+    assert!(source.is::<ObjError>());
+    assert!(source.source().unwrap().is::<std::io::Error>());
+}
+
+#[test]
+fn load_broken_mtls() {
+    let dir = temp_dir("obj_load_broken_mtls");
+    fs::write(
+        dir.join("scene.obj"),
+        "mtllib broken.mtl\nmtllib missing.mtl\nmtllib good.mtl\n",
+    )
+    .unwrap();
+    fs::write(dir.join("broken.mtl"), "newmtl broken\nKd 1 x 0\n").unwrap();
+    fs::write(dir.join("good.mtl"), "newmtl good\nKd 1 1 0\n").unwrap();
+    let _ = fs::remove_file(dir.join("missing.mtl"));
+
+    let mut obj = Obj::load(dir.join("scene.obj")).unwrap();
+    let errors = match obj.load_mtls() {
+        Err(Error::MaterialLibraries(errors)) => errors,
+        other => panic!("expected material library errors, got {:?}", other),
+    };
+
+    assert_eq!(errors.len(), 2);
+    match errors[0] {
+        Error::Mtl {
+            ref path,
+            operation: Operation::Load,
+            ref error,
+        } => {
+            assert_eq!(path, &dir.join("broken.mtl"));
+            assert!(matches!(error.kind(), MtlError::InvalidValue(_)));
+            let location = error.location().unwrap();
+            assert_eq!(location.path.as_ref(), Some(path));
+            assert_eq!(location.line_number, 2);
+        }
+        ref other => panic!("expected an mtl load error, got {:?}", other),
+    }
+    match errors[1] {
+        Error::Mtl {
+            ref path,
+            operation: Operation::Resolve,
+            error: MtlError::Io(_),
+        } => assert_eq!(path, &dir.join("missing.mtl")),
+        ref other => panic!("expected an mtl resolve error, got {:?}", other),
+    }
+    assert_eq!(obj.data.material_libs[2].materials.len(), 1);
+}
+
+#[test]
+fn save_to_missing_directory() {
+    let path = temp_dir("obj_save_to_missing_directory")
+        .join("missing")
+        .join("out.obj");
+    let err = Obj {
+        data: Default::default(),
+        path: PathBuf::new(),
+    }
+    .save(&path)
+    .unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::Obj {
+            operation: Operation::Save,
+            error: ObjError::Io(_),
+            ..
+        }
+    ));
+    assert_eq!(err.to_string(), format!("Failed to save {}", path.display()));
+}
@@ -0,0 +1,129 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{CurveRef, CurveType, IndexTuple, ObjData, ObjError, ParamVertex};
+
+static TRIMMED_PATCH: &str = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 1
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 1
+vp 0.1 0.1
+vp 0.9 0.1
+vp 0.9 0.9
+vp 0.1 0.9
+vp 0.1 0.1
+vp 0.5 0.5 1
+
+cstype bezier
+deg 1
+curv2 1 2 3 4 5
+parm u 0 1 2 3 4
+end
+
+cstype rat bspline
+deg 1 1
+surf 0 1 0 1 1/1 2/2 3/3 4/4
+parm u 0 0 1 1
+parm v 0 0 1 1
+trim 0 4 -1
+hole 0 1 1 1 2 1
+sp 6
+end
+
+cstype bmatrix
+deg 1
+bmat u 1 -1 0 1
+step 1
+curv 0 1 1 4
+end
+";
+
+#[test]
+fn free_form_elements() {
+    let obj = ObjData::load_buf(TRIMMED_PATCH.as_bytes()).unwrap();
+
+    assert_eq!(obj.parameter.len(), 6);
+    assert_eq!(obj.parameter[5], ParamVertex(0.5, Some(0.5), Some(1.0)));
+
+    let curve2 = &obj.free_form.curves2[0];
+    assert_eq!(curve2.attributes.cstype, Some(CurveType::Bezier));
+    assert_eq!(curve2.attributes.degree, Some((1, None)));
+    assert_eq!(curve2.vertices, vec![0, 1, 2, 3, 4]);
+    assert_eq!(curve2.body.parm_u, vec![0., 1., 2., 3., 4.]);
+
+    let surface = &obj.free_form.surfaces[0];
+    assert!(surface.attributes.rational);
+    assert_eq!(surface.attributes.cstype, Some(CurveType::BSpline));
+    assert_eq!(surface.attributes.degree, Some((1, Some(1))));
+    assert_eq!(surface.range_u, [0., 1.]);
+    assert_eq!(surface.range_v, [0., 1.]);
+    assert_eq!(surface.vertices[3], IndexTuple(3, Some(3), None));
+    assert_eq!(surface.body.parm_v, vec![0., 0., 1., 1.]);
+    assert_eq!(
+        surface.body.trims,
+        vec![vec![CurveRef {
+            range: [0., 4.],
+            curve: 0
+        }]]
+    );
+    assert_eq!(surface.body.holes[0].len(), 2);
+    assert_eq!(surface.body.special_points, vec![5]);
+
+    let curve = &obj.free_form.curves[0];
+    assert_eq!(curve.attributes.cstype, Some(CurveType::BasisMatrix));
+    assert_eq!(curve.attributes.basis_u, Some(vec![1., -1., 0., 1.]));
+    assert_eq!(curve.attributes.step, Some((1., None)));
+    assert_eq!(curve.range, [0., 1.]);
+    assert_eq!(curve.vertices, vec![0, 3]);
+
+    // Trimming curves and special points keep referring to the same elements when read back.
+    let mut out = Vec::new();
+    obj.write_to_buf(&mut out).unwrap();
+    assert_eq!(ObjData::load_buf(out.as_slice()).unwrap(), obj);
+}
+
+#[test]
+fn free_form_errors() {
+    assert!(matches!(
+        ObjData::load_buf("vp 0 0\nparm u 0 1\n".as_bytes()).unwrap_err().kind(),
+        ObjError::FreeFormStatementOutsideElement { line_number: 2, .. }
+    ));
+    assert!(matches!(
+        ObjData::load_buf("vp 0 0\ncurv2 1\nend\nend\n".as_bytes())
+            .unwrap_err()
+            .kind(),
+        ObjError::FreeFormStatementOutsideElement { line_number: 4, .. }
+    ));
+    assert!(matches!(
+        ObjData::load_buf("cstype nurbs\n".as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 1, .. }
+    ));
+    assert!(matches!(
+        ObjData::load_buf("v 0 0 0\ncurv 0 1 0\n".as_bytes())
+            .unwrap_err()
+            .kind(),
+        ObjError::ZeroVertexNumber { line_number: 2 }
+    ));
+    assert!(matches!(
+        ObjData::load_buf("vp 0 0\ncurv2 1\ntrim 0 1\n".as_bytes())
+            .unwrap_err()
+            .kind(),
+        ObjError::ArgumentListFailure { line_number: 3, .. }
+    ));
+}
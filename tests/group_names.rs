@@ -0,0 +1,57 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IndexTuple, LoadConfig, ObjData, SimplePolygon};
+
+fn face(a: usize, b: usize, c: usize) -> SimplePolygon {
+    SimplePolygon(vec![
+        IndexTuple(a, None, None),
+        IndexTuple(b, None, None),
+        IndexTuple(c, None, None),
+    ])
+}
+
+#[test]
+fn faces_in_any_named_group() {
+    let input = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\ng left arm\nf 1 2 3\ng right arm\nf 1 3 4\ng torso\nf 2 3 4\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    let groups = &obj.objects[0].groups;
+    assert_eq!(groups[0].name, "left");
+    assert_eq!(groups[0].names().collect::<Vec<_>>(), vec!["left", "arm"]);
+    assert!(groups[2].additional_names.is_empty());
+    assert_eq!(
+        obj.faces_in_group("arm").collect::<Vec<_>>(),
+        vec![&face(0, 1, 2), &face(0, 2, 3)]
+    );
+    assert_eq!(obj.faces_in_group("left arm").count(), 0);
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    assert!(String::from_utf8(output).unwrap().contains("g left arm\nf 1 2 3\n"));
+}
+
+#[test]
+fn single_group_name() {
+    let config = LoadConfig {
+        single_group_name: true,
+        ..LoadConfig::default()
+    };
+    let obj = ObjData::load_buf_with_config("v 0 0 0\ng left arm\nf 1 1 1\n".as_bytes(), config).unwrap();
+
+    let group = &obj.objects[0].groups[0];
+    assert_eq!(group.name, "left arm");
+    assert!(group.additional_names.is_empty());
+    assert_eq!(obj.faces_in_group("arm").count(), 0);
+}
@@ -0,0 +1,72 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{ObjData, ObjError, TextureDim};
+
+fn round_trip(obj: &ObjData) -> ObjData {
+    let mut out = Vec::new();
+    obj.write_to_buf(&mut out).unwrap();
+    ObjData::load_buf(out.as_slice()).unwrap()
+}
+
+#[test]
+fn position_w() {
+    let obj = ObjData::load_buf("v 0 0 0\nv 1 0 0 0.5\nv 0 1 0 1 0.5 0.5 0.5\n".as_bytes());
+    assert!(matches!(
+        obj.unwrap_err().kind(),
+        ObjError::InconsistentVertexColors { line_number: 3 }
+    ));
+
+    let obj = ObjData::load_buf("v 0 0 0\nv 1 0 0 0.5\nv 0 1 0\n".as_bytes()).unwrap();
+    assert_eq!(obj.position, vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+    assert_eq!(obj.position_w, vec![1., 0.5, 1.]);
+    assert_eq!(round_trip(&obj), obj);
+
+    let obj = ObjData::load_buf("v 0 0 0 2 1 1 1\nv 1 0 0 1 0 0\n".as_bytes()).unwrap();
+    assert_eq!(obj.position_w, vec![2., 1.]);
+    assert_eq!(obj.color, vec![[1., 1., 1.], [1., 0., 0.]]);
+    assert_eq!(round_trip(&obj), obj);
+
+    let obj = ObjData::load_buf("v 0 0 0\n".as_bytes()).unwrap();
+    assert!(obj.position_w.is_empty());
+}
+
+#[test]
+fn texture_dimensions() {
+    let obj = ObjData::load_buf("vt 0.5\nvt 0.25\n".as_bytes()).unwrap();
+    assert_eq!(obj.texture_dim, TextureDim::U);
+    assert_eq!(obj.texture, vec![[0.5, 0.], [0.25, 0.]]);
+    assert!(obj.texture_w.is_empty());
+    assert_eq!(round_trip(&obj), obj);
+
+    let obj = ObjData::load_buf("vt 0.5\nvt 0.25 0.75\n".as_bytes()).unwrap();
+    assert_eq!(obj.texture_dim, TextureDim::UV);
+    assert_eq!(obj.texture, vec![[0.5, 0.], [0.25, 0.75]]);
+    assert_eq!(round_trip(&obj), obj);
+
+    let obj = ObjData::load_buf("vt 0.5 0.5\nvt 0.25 0.75 1\n".as_bytes()).unwrap();
+    assert_eq!(obj.texture_dim, TextureDim::UVW);
+    assert_eq!(obj.texture, vec![[0.5, 0.5], [0.25, 0.75]]);
+    assert_eq!(obj.texture_w, vec![0., 1.]);
+    assert_eq!(round_trip(&obj), obj);
+
+    assert!(matches!(
+        ObjData::load_buf("vt\n".as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 1, .. }
+    ));
+    assert!(matches!(
+        ObjData::load_buf("vt 0 0 0 0\n".as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 1, .. }
+    ));
+}
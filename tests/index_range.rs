@@ -0,0 +1,120 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IndexKind, IndexTuple, LoadConfig, ObjData, ObjError};
+
+fn load_err(input: &str, config: LoadConfig) -> ObjError {
+    ObjData::load_buf_with_config(input.as_bytes(), config).unwrap_err()
+}
+
+#[test]
+fn index_out_of_range() {
+    let config = LoadConfig::default();
+    assert!(matches!(
+        load_err("v 0 0 0\nv 1 0 0\nf -10 1 2\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 3,
+            kind: IndexKind::Position,
+            index: -10,
+            len: 2,
+        }
+    ));
+    assert!(matches!(
+        load_err("v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 4,
+            kind: IndexKind::Position,
+            index: 4,
+            len: 3,
+        }
+    ));
+    assert!(matches!(
+        load_err("v 0 0 0\nv 1 0 0\nv 1 1 0\nvt 0 0\nf 1/1 2/2 3/1\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 5,
+            kind: IndexKind::Texture,
+            index: 2,
+            len: 1,
+        }
+    ));
+    assert!(matches!(
+        load_err("v 0 0 0\nv 1 0 0\nvn 0 0 1\nl 1 -3\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 4,
+            kind: IndexKind::Position,
+            index: -3,
+            len: 2,
+        }
+    ));
+    assert!(matches!(
+        load_err("vp 0 0\ncurv2 1 2\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 2,
+            kind: IndexKind::Parameter,
+            index: 2,
+            len: 1,
+        }
+    ));
+}
+
+#[test]
+fn forward_references() {
+    let config = LoadConfig {
+        forward_references: true,
+        ..LoadConfig::default()
+    };
+    let obj = ObjData::load_buf_with_config("f 1/1 2/1 -1\nv 0 0 0\nv 1 0 0\nvt 0 0\nv 1 1 0\n".as_bytes(), config);
+    assert!(matches!(
+        obj.unwrap_err().kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 1,
+            kind: IndexKind::Position,
+            index: -1,
+            len: 0,
+        }
+    ));
+
+    let obj =
+        ObjData::load_buf_with_config("f 1/1 2/1 3\nv 0 0 0\nv 1 0 0\nvt 0 0\nv 1 1 0\n".as_bytes(), config).unwrap();
+    assert_eq!(
+        obj.objects[0].groups[0].polys[0].0,
+        vec![
+            IndexTuple(0, Some(0), None),
+            IndexTuple(1, Some(0), None),
+            IndexTuple(2, None, None)
+        ]
+    );
+
+    // Forward references that remain out of range are reported with the line of the element.
+    assert!(matches!(
+        load_err("v 0 0 0\nf 1 2 3/1/4\nv 1 0 0\nv 1 1 0\nvt 0 0\n", config).kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 2,
+            kind: IndexKind::Normal,
+            index: 4,
+            len: 0,
+        }
+    ));
+    let (_, errors) = ObjData::load_buf_recovering("f 1 2 3\nf 4 5 6\nv 0 0 0\nv 1 0 0\n".as_bytes(), config);
+    assert_eq!(errors.len(), 4);
+    assert!(matches!(
+        errors[0].kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 1,
+            index: 3,
+            len: 2,
+            ..
+        }
+    ));
+}
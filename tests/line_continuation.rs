@@ -0,0 +1,71 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IndexTuple, Mtl, ObjData, ObjError, SimplePolygon};
+
+static CONTINUED_SQUARE: &str = "v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+f 1 2 \\
+  3 \\
+4
+f 1 \\
+ x 3
+";
+
+#[test]
+fn load_continued_face() {
+    let input = CONTINUED_SQUARE.replace("f 1 \\\n x 3\n", "");
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    let polys = &obj.objects[0].groups[0].polys;
+    assert_eq!(
+        polys,
+        &vec![SimplePolygon(vec![
+            IndexTuple(0, None, None),
+            IndexTuple(1, None, None),
+            IndexTuple(2, None, None),
+            IndexTuple(3, None, None),
+        ])]
+    );
+}
+
+#[test]
+fn continued_line_number() {
+    match ObjData::load_buf(CONTINUED_SQUARE.as_bytes()).unwrap_err().kind() {
+        ObjError::MalformedFaceGroup { line_number: 8, group } => assert_eq!(group, "x"),
+        other => panic!("expected a malformed face group error, got {:?}", other),
+    }
+}
+
+#[test]
+fn load_continued_material() {
+    let input = "newmtl red\nKd 1 \\\n0 0\nNs \\\n  10\n";
+    let mut mtl = Mtl::new("red.mtl".to_string());
+    mtl.reload(input.as_bytes()).unwrap();
+
+    assert_eq!(mtl.materials[0].kd, Some([1.0, 0.0, 0.0]));
+    assert_eq!(mtl.materials[0].ns, Some(10.0));
+}
+
+#[test]
+fn comment_is_not_continued() {
+    let obj = ObjData::load_buf("# C:\\dir\\\nv 0 0 0\n".as_bytes()).unwrap();
+    assert_eq!(obj.position, vec![[0.0, 0.0, 0.0]]);
+
+    let mut mtl = Mtl::new("red.mtl".to_string());
+    mtl.reload("# C:\\dir\\\nnewmtl red\n".as_bytes()).unwrap();
+    assert_eq!(mtl.materials.len(), 1);
+}
@@ -0,0 +1,78 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use std::fs;
+
+use obj::{IndexTuple, Mtl, ObjData, SimplePolygon};
+
+static SQUARE: &str = "# square\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 \\\n3 4\n";
+
+fn load_mtl(input: &[u8]) -> Mtl {
+    let mut mtl = Mtl::new("sponza.mtl".to_string());
+    mtl.reload(input).unwrap();
+    mtl
+}
+
+#[test]
+fn obj_line_endings() {
+    let expected = ObjData::load_buf(SQUARE.as_bytes()).unwrap();
+    assert_eq!(
+        expected.objects[0].groups[0].polys,
+        vec![SimplePolygon(vec![
+            IndexTuple(0, None, None),
+            IndexTuple(1, None, None),
+            IndexTuple(2, None, None),
+            IndexTuple(3, None, None),
+        ])]
+    );
+
+    for ending in &["\r\n", "\r"] {
+        let input = SQUARE.replace('\n', ending);
+        assert_eq!(ObjData::load_buf(input.as_bytes()).unwrap(), expected, "{:?}", ending);
+    }
+}
+
+#[test]
+fn obj_line_numbers_with_cr() {
+    let input = SQUARE.replace("f 1 2 \\\n3 4", "f 1 2 \\\n3 5").replace('\n', "\r");
+    let err = ObjData::load_buf(input.as_bytes()).unwrap_err();
+    assert_eq!(err.location().unwrap().line_number, 6);
+}
+
+#[test]
+fn obj_bom() {
+    let expected = ObjData::load_buf(SQUARE.as_bytes()).unwrap();
+    let input = format!("\u{feff}{}", SQUARE.replace('\n', "\r\n"));
+    // Strict mode would reject a keyword with the byte order mark attached.
+    assert_eq!(ObjData::load_buf(input.as_bytes()).unwrap(), expected);
+
+    let input = b"\xef\xbb\xbfv 0 0 0\np 1\n";
+    assert_eq!(ObjData::load_buf(input.as_ref()).unwrap().position.len(), 1);
+}
+
+#[test]
+fn mtl_line_endings() {
+    let original = fs::read("test_assets/sponza.mtl").unwrap();
+    let expected = load_mtl(&original);
+    assert!(expected.materials.len() > 1);
+
+    let text = String::from_utf8(original).unwrap().replace("\r\n", "\n");
+    assert_eq!(load_mtl(text.as_bytes()), expected);
+    assert_eq!(load_mtl(text.replace('\n', "\r").as_bytes()), expected);
+
+    let bom = format!("\u{feff}{}", text.replace('\n', "\r\n"));
+    assert_eq!(load_mtl(bom.as_bytes()), expected);
+    let bom = format!("\u{feff}{}", text.replace('\n', "\r"));
+    assert_eq!(load_mtl(bom.as_bytes()), expected);
+}
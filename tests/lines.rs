@@ -12,7 +12,7 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
-use obj::{Group, Line, LineTuple, ObjData, Object};
+use obj::{Group, Line, LineTuple, ObjData, ObjMaterial, Object, Point};
 use std::io::BufReader;
 
 /// Test that [`std::fmt::Display`] is implemented correctly for
@@ -57,7 +57,7 @@ fn test_load_line() {
 }
 
 #[test]
-#[should_panic(expected = "LineHasNormalIndex { line_number: 5 }")]
+#[should_panic(expected = "LineHasNormalIndex { line_number: 6 }")]
 fn test_load_line_with_normal() {
     let line_with_normals = "
     v 0 0 0
@@ -73,8 +73,10 @@ fn test_load_line_with_normal() {
 
 #[test]
 fn test_export_line() {
-    let mut obj_data: ObjData = ObjData::default();
-    obj_data.position = vec![[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0], [1.0, 0.0, 0.0]];
+    let mut obj_data = ObjData {
+        position: vec![[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0], [1.0, 0.0, 0.0]],
+        ..ObjData::default()
+    };
 
     let name = "line test".to_string();
     let mut object = Object::new(name.to_owned());
@@ -98,3 +100,53 @@ fn test_export_line() {
     let expected_output = "# Generated by the obj Rust library (https://crates.io/crates/obj).\nv 0 0 0\nv 0 1 0\nv 1 1 0\nv 1 0 0\no line test\ng line test\nl 1 2 3 4 1\n".to_string();
     assert_eq!(output, expected_output);
 }
+
+#[test]
+fn test_load_point() {
+    let point_cloud = "
+    v 0 0 0
+    v 0 1 0
+    v 1 1 0
+    p 1 2
+    usemtl red
+    p -1
+    ";
+
+    let obj = ObjData::load_buf(point_cloud.as_bytes()).unwrap();
+
+    // A material change starts a new group, like it does for faces and lines.
+    let groups = &obj.objects[0].groups;
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].points, vec![Point(vec![0, 1])]);
+    assert_eq!(groups[0].material, None);
+    assert_eq!(groups[1].points, vec![Point(vec![2])]);
+    assert_eq!(groups[1].material, Some(ObjMaterial::Ref("red".to_string())));
+}
+
+#[test]
+#[should_panic(expected = "ZeroVertexNumber { line_number: 2 }")]
+fn test_load_point_with_zero_index() {
+    let _ = ObjData::load_buf("v 0 0 0\np 0".as_bytes()).unwrap();
+}
+
+#[test]
+fn test_export_point() {
+    let mut obj_data = ObjData {
+        position: vec![[0.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        ..ObjData::default()
+    };
+
+    let name = "point test".to_string();
+    let mut object = Object::new(name.to_owned());
+    let mut group = Group::new(name.to_owned());
+    group.points = vec![Point(vec![0, 1]), Point(vec![1])];
+    object.groups.push(group);
+    obj_data.objects.push(object);
+
+    let mut output = Vec::new();
+    obj_data.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let expected_output = "# Generated by the obj Rust library (https://crates.io/crates/obj).\nv 0 0 0\nv 0 1 0\no point test\ng point test\np 1 2\np 2\n".to_string();
+    assert_eq!(output, expected_output);
+}
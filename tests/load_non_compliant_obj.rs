@@ -12,12 +12,12 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 
-use obj::{LoadConfig, ObjData};
+use obj::{Group, LoadConfig, ObjData, ObjError, Object, StatementHandler};
 use std::io::BufReader;
 
 /// This is an example of an obj file augmented with additional custom commands.
 /// We expect to be able to load the recognizable parts of these kinds of files.
-static SQUARE_EXTENDED: &'static str = "
+static SQUARE_EXTENDED: &str = "
 scale 1
 vt 0 0
 adjf 0 1
@@ -49,7 +49,7 @@ f 3/4 1/1 4/3
 ";
 
 /// This is the strictly spec compliant version of `SQUARE_EXTENDED`.
-static SQUARE_STRICT: &'static str = "
+static SQUARE_STRICT: &str = "
 vt 0 0
 vt 1 0
 vt 1 1
@@ -64,7 +64,10 @@ f 3/4 1/1 4/3
 
 #[test]
 fn load_square_non_compliant() {
-    let permissive_config = LoadConfig { strict: false };
+    let permissive_config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
 
     // Load the extended version of the square
     let mut reader = BufReader::new(SQUARE_EXTENDED.as_bytes());
@@ -76,8 +79,98 @@ fn load_square_non_compliant() {
 
     assert_eq!(obj_basic, obj_ext);
 
-    let strict_config = LoadConfig { strict: true };
+    let strict_config = LoadConfig {
+        strict: true,
+        ..LoadConfig::default()
+    };
 
     let mut reader = BufReader::new(SQUARE_EXTENDED.as_bytes());
     assert!(ObjData::load_buf_with_config(&mut reader, strict_config).is_err());
 }
+
+/// Collects the `scale` and `adjf` extensions and leaves all other statements to the loader.
+#[derive(Default)]
+struct SquareExtensions {
+    scale: Option<f32>,
+    face_adjacency: Vec<(usize, Vec<usize>)>,
+}
+
+impl StatementHandler for SquareExtensions {
+    fn handle(
+        &mut self,
+        line_number: usize,
+        keyword: &str,
+        args: &[&str],
+        _object: &mut Object,
+        group: Option<&mut Group>,
+    ) -> Result<bool, ObjError> {
+        let failure = || ObjError::ArgumentListFailure {
+            line_number,
+            list: args.join(" "),
+        };
+        match keyword {
+            "scale" => self.scale = Some(args.first().and_then(|s| s.parse().ok()).ok_or_else(failure)?),
+            "adjf" => {
+                let faces = args
+                    .iter()
+                    .map(|a| a.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| failure())?;
+                self.face_adjacency.push((group.map_or(0, |g| g.polys.len()), faces));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[test]
+fn load_square_extensions() {
+    let permissive_config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
+
+    let mut extensions = SquareExtensions::default();
+    let obj_ext =
+        ObjData::load_buf_with_handler(SQUARE_EXTENDED.as_bytes(), permissive_config, &mut extensions).unwrap();
+    let obj_basic = ObjData::load_buf_with_config(SQUARE_STRICT.as_bytes(), permissive_config).unwrap();
+
+    assert_eq!(obj_basic, obj_ext);
+    assert_eq!(extensions.scale, Some(1.0));
+    assert_eq!(extensions.face_adjacency.len(), 4);
+    assert_eq!(extensions.face_adjacency[0], (0, vec![0, 1]));
+
+    // Statements that the handler doesn't accept are still rejected in strict mode.
+    let mut extensions = SquareExtensions::default();
+    let err = ObjData::load_buf_with_handler(SQUARE_EXTENDED.as_bytes(), LoadConfig::default(), &mut extensions);
+    match err.unwrap_err().kind() {
+        ObjError::UnexpectedCommand {
+            line_number: 12,
+            command,
+        } => assert_eq!(command, "ny"),
+        other => panic!("expected an unexpected command error, got {:?}", other),
+    }
+
+    let mut extensions = SquareExtensions::default();
+    let err = ObjData::load_buf_with_handler("scale x".as_bytes(), LoadConfig::default(), &mut extensions);
+    match err.unwrap_err().kind() {
+        ObjError::ArgumentListFailure { line_number: 1, list } => assert_eq!(list, "x"),
+        other => panic!("expected an argument list failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn load_trailing_comments() {
+    let input = "v 1 2 3 # comment\nv 4 5 6 1 #comment\nvt 0.5 0.5 # uv\nvn 0 0 1 # normal\nf 1/1/1 2/1/1 1/1/1\n";
+    for &strict in &[false, true] {
+        let config = LoadConfig {
+            strict,
+            ..LoadConfig::default()
+        };
+        let obj = ObjData::load_buf_with_config(input.as_bytes(), config).unwrap();
+        assert_eq!(obj.position, vec![[1., 2., 3.], [4., 5., 6.]]);
+        assert_eq!(obj.texture, vec![[0.5, 0.5]]);
+        assert_eq!(obj.normal, vec![[0., 0., 1.]]);
+    }
+}
@@ -17,5 +17,5 @@ use obj::Obj;
 #[test]
 fn load_test_file() {
     let mut sponza = Obj::load("test_assets/sponza.obj").unwrap();
-    let _ = sponza.load_mtls().unwrap();
+    sponza.load_mtls().unwrap();
 }
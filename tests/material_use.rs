@@ -15,7 +15,7 @@
 use obj::ObjData;
 use std::io::BufReader;
 
-static SQUARE: &'static str = "
+static SQUARE: &str = "
 v 0 0 0
 v 1 1 1
 v 1 0 1
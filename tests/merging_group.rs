@@ -0,0 +1,78 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{MergingGroup, ObjData, ObjError};
+
+#[test]
+fn merging_group_resolution() {
+    let mg = |id, resolution| Some(MergingGroup { id, resolution });
+    for (statement, merging_group) in &[
+        ("mg 1 0.5", mg(1, 0.5)),
+        ("mg 7 1e-3", mg(7, 0.001)),
+        ("mg off", None),
+        ("mg 0", None),
+        // The resolution of group 0 doesn't matter, since it turns merging off.
+        ("mg 0 0.5", None),
+    ] {
+        let input = format!("v 0 0 0\n{}\nf 1 1 1\n", statement);
+        let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+        assert_eq!(obj.objects[0].groups[0].merging_group, *merging_group, "{}", statement);
+    }
+
+    for (statement, list) in &[("mg 1", "1"), ("mg 1 fine", "1 fine"), ("mg off 1", "off 1")] {
+        match ObjData::load_buf(statement.as_bytes()).unwrap_err().kind() {
+            ObjError::ArgumentListFailure {
+                line_number: 1,
+                list: l,
+            } => assert_eq!(l, list),
+            other => panic!("expected an argument list failure for {:?}, got {:?}", statement, other),
+        }
+    }
+}
+
+#[test]
+fn merging_group_changes_split_groups() {
+    let input = "v 0 0 0\ng quads\nf 1 1 1\nmg 2 0.25\nf 1 1 1\ng other\nf 1 1 1\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    let mg = Some(MergingGroup {
+        id: 2,
+        resolution: 0.25,
+    });
+    let groups: Vec<_> = obj.objects[0]
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.index, g.merging_group))
+        .collect();
+    assert_eq!(groups, vec![("quads", 0, None), ("quads", 1, mg), ("other", 0, mg)]);
+}
+
+#[test]
+fn surface_merging_groups() {
+    let surface = "surf 0 1 0 1 1 2 3 4\nparm u 0 1\nparm v 0 1\nend\n";
+    let input = format!(
+        "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\ncstype bezier\ndeg 1 1\nmg 1 0.5\n{0}mg off\n{0}",
+        surface
+    );
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    let surfaces: Vec<_> = obj.free_form.surfaces.iter().map(|s| s.merging_group).collect();
+    assert_eq!(surfaces, vec![Some(MergingGroup { id: 1, resolution: 0.5 }), None]);
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("mg 1 0.5\nsurf 0 1 0 1 1 2 3 4\n"), "{}", output);
+    assert_eq!(ObjData::load_buf(output.as_bytes()).unwrap(), obj);
+}
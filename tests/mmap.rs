@@ -0,0 +1,98 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+#![cfg(feature = "memmap")]
+
+use obj::{Error, LoadConfig, Obj, Operation};
+use std::{fs, path::PathBuf};
+
+static CUBE: &str = "
+mtllib cube.mtl
+o cube
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 -1
+g front
+usemtl red
+f 1//1 2//1 3//1 4//1
+";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn load_mmap_like_load() {
+    let path = temp_dir("obj_load_mmap").join("cube.obj");
+    fs::write(&path, CUBE).unwrap();
+
+    let mapped = Obj::load_mmap(&path, LoadConfig::default()).unwrap();
+    let read = Obj::load_with_config(&path, LoadConfig::default()).unwrap();
+    assert_eq!(mapped.data, read.data);
+    assert_eq!(mapped.path, read.path);
+    assert_eq!(mapped.data.objects[0].groups[0].polys.len(), 1);
+}
+
+#[test]
+fn load_mmap_empty_and_broken() {
+    let dir = temp_dir("obj_load_mmap_empty_and_broken");
+    let empty = dir.join("empty.obj");
+    fs::write(&empty, "").unwrap();
+    assert_eq!(
+        Obj::load_mmap(&empty, LoadConfig::default())
+            .unwrap()
+            .data
+            .position
+            .len(),
+        0
+    );
+
+    let broken = dir.join("broken.obj");
+    fs::write(&broken, "v 0 0 0\nf 1 2 3\n").unwrap();
+    let err = match Obj::load_mmap(&broken, LoadConfig::default()) {
+        Err(Error::Obj {
+            path,
+            operation: Operation::Load,
+            error,
+        }) if path == broken => error,
+        other => panic!("unexpected result {:?}", other),
+    };
+    let location = err.location().unwrap();
+    assert_eq!(location.path.as_deref(), Some(broken.as_path()));
+    assert_eq!(location.line_number, 2);
+
+    assert!(Obj::load_mmap(dir.join("missing.obj"), LoadConfig::default()).is_err());
+}
+
+#[test]
+#[cfg(unix)]
+fn load_mmap_from_pipe() {
+    let path = temp_dir("obj_load_mmap_from_pipe").join("cube.obj");
+    let _ = fs::remove_file(&path);
+    let status = std::process::Command::new("mkfifo").arg(&path).status().unwrap();
+    assert!(status.success());
+
+    let writer = std::thread::spawn({
+        let path = path.clone();
+        move || fs::write(path, CUBE).unwrap()
+    });
+    let obj = Obj::load_mmap(&path, LoadConfig::default()).unwrap();
+    writer.join().unwrap();
+    assert_eq!(obj.data.position.len(), 4);
+    assert_eq!(obj.data.material_libs.len(), 1);
+}
@@ -0,0 +1,104 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+#![cfg(feature = "rayon")]
+
+use obj::{LoadConfig, ObjData};
+use std::fmt::Write;
+
+/// A file of several megabytes, so that it is split into many chunks, using most statements that
+/// depend on the ones before them.
+fn large(line_end: &str) -> String {
+    let mut out = String::from("\u{feff}# generated\n");
+    for i in 0..32_000 {
+        if i % 1000 == 0 {
+            writeln!(out, "o part{}\ng side {}\nusemtl mat{}\ns {}", i, i % 3, i % 7, i % 2).unwrap();
+        }
+        if i % 777 == 0 {
+            writeln!(out, "v {} \\\n  {} {}", i, -i, 0.5).unwrap();
+        } else {
+            writeln!(out, "v {} {} {}", i as f32 * 0.1, (i as f32).sin(), -1.25e-3).unwrap();
+        }
+        writeln!(out, "vt {} {}", i as f32 / 3.0, 0.25).unwrap();
+        writeln!(out, "vn 0 0 1").unwrap();
+        if i > 2 {
+            writeln!(out, "f -3/-3/-3 -2/-2/-2 -1/-1/-1 {0}/{0}/{0}", i - 2).unwrap();
+            writeln!(out, "f {0}//{0} {1}//{1} {2}//{2}", i - 2, i - 1, i).unwrap();
+            writeln!(out, "l {} {}\n\nmg 1 0.5\np {}", i - 1, i, i).unwrap();
+        }
+    }
+    out.replace('\n', line_end)
+}
+
+fn assert_same(input: &str, config: LoadConfig) {
+    let serial = ObjData::load_buf_with_config(input.as_bytes(), config);
+    let parallel = ObjData::load_buf_parallel(input.as_bytes(), config);
+    match (serial, parallel) {
+        (Ok(serial), Ok(parallel)) => assert!(serial == parallel),
+        (Err(serial), Err(parallel)) => assert_eq!(serial.to_string(), parallel.to_string()),
+        (serial, parallel) => panic!("serial: {:?}\nparallel: {:?}", serial.err(), parallel.err()),
+    }
+}
+
+#[test]
+fn same_as_serial() {
+    let input = large("\n");
+    assert!(input.len() > 4 << 20);
+    assert_same(&input, LoadConfig::default());
+    assert_same(&large("\r\n"), LoadConfig::default());
+    assert_same(&large("\r"), LoadConfig::default());
+    assert_same("", LoadConfig::default());
+    assert_same("v 0 0 0\nf 1 1 1", LoadConfig::default());
+    assert_same(
+        "v 0 0 0 # c\nvt 1 #c\nvn 0 0 1 # c\nf 1/1/1 1/1/1 1/1/1",
+        LoadConfig::default(),
+    );
+}
+
+#[test]
+fn same_errors_as_serial() {
+    for line_end in &["\n", "\r\n", "\r"] {
+        let input = large(line_end);
+        let split = input[..input.len() / 2].rfind(line_end).unwrap() + line_end.len();
+        for error in &[
+            "f 1 2 100000000",
+            "f 1 x 2",
+            "v 1 2",
+            "vn 1 2 nan?",
+            "v 1 2 3 1 1 1",
+            "f 0 1 2",
+        ] {
+            assert_same(
+                &format!("{}{}{}{}", &input[..split], error, line_end, &input[split..]),
+                LoadConfig::default(),
+            );
+        }
+        assert_same(&format!("{}f 1 2 -100000000", input), LoadConfig::default());
+        assert_same(
+            &format!("v 0 0 0{0}v 0 0 0{0}f 1 2 5{0}", line_end),
+            LoadConfig::default(),
+        );
+    }
+}
+
+#[test]
+fn same_forward_references_as_serial() {
+    let input = format!("f 1 2 3\nf 1 2 100000\n{}", large("\n"));
+    let config = LoadConfig {
+        forward_references: true,
+        ..LoadConfig::default()
+    };
+    assert_same(&input, config);
+    assert_same(&input[16..], config);
+}
@@ -0,0 +1,166 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{LoadConfig, ObjData, PreservedLine, PreservedPosition};
+
+/// An artist file in the form written by `ObjData::write_to_buf`, with comments and vendor
+/// statements in between.
+static ANNOTATED_QUADS: &str = "# Exported by SomeTool 3.1
+# units: cm
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+o quads
+# object comment
+g front
+usemtl paint
+# first face
+f 1 2 3
+lod_hint 2
+f 1 3 4
+# trailing comment
+g back
+usemtl paint
+f 3 2 1
+l 1 2
+# before point
+p 4
+";
+
+fn preserving_config() -> LoadConfig {
+    LoadConfig {
+        strict: false,
+        preserve_lines: true,
+        ..LoadConfig::default()
+    }
+}
+
+#[test]
+fn load_preserved_lines() {
+    let obj = ObjData::load_buf_with_config(ANNOTATED_QUADS.as_bytes(), preserving_config()).unwrap();
+
+    assert_eq!(
+        obj.header,
+        Some(vec![
+            "# Exported by SomeTool 3.1".to_string(),
+            "# units: cm".to_string()
+        ])
+    );
+
+    let object = &obj.objects[0];
+    assert_eq!(object.preserved, vec!["# object comment"]);
+
+    let line = |position, text: &str| PreservedLine {
+        position,
+        text: text.to_string(),
+    };
+    assert_eq!(
+        object.groups[0].preserved,
+        vec![
+            line(PreservedPosition::Poly(0), "# first face"),
+            line(PreservedPosition::Poly(1), "lod_hint 2"),
+            line(PreservedPosition::End, "# trailing comment")
+        ]
+    );
+    assert_eq!(
+        object.groups[1].preserved,
+        vec![line(PreservedPosition::Point(0), "# before point")]
+    );
+}
+
+#[test]
+fn round_trip_preserved_lines() {
+    let obj = ObjData::load_buf_with_config(ANNOTATED_QUADS.as_bytes(), preserving_config()).unwrap();
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), ANNOTATED_QUADS);
+}
+
+#[test]
+fn round_trip_preserved_lines_in_statement_order() {
+    let input = "v 0 0 0
+v 1 0 0
+v 1 1 0
+o empty
+# object without groups
+o mixed
+g lines
+# before material
+usemtl paint
+f 1 2 3
+# before line
+l 1 2
+# before point
+p 3
+";
+    let obj = ObjData::load_buf_with_config(input.as_bytes(), preserving_config()).unwrap();
+    assert_eq!(obj.objects[0].name, "empty");
+    assert_eq!(obj.objects[0].preserved, vec!["# object without groups"]);
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+
+    // Elements of different kinds are written apart, with each line before its element.
+    let input = "v 0 0 0
+g mixed
+f 1 1 1
+l 1 1
+# second face
+f 1 1 1
+p 1
+# end
+";
+    let obj = ObjData::load_buf_with_config(input.as_bytes(), preserving_config()).unwrap();
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "v 0 0 0
+g mixed
+f 1 1 1
+# second face
+f 1 1 1
+l 1 1
+p 1
+# end
+"
+    );
+    assert_eq!(
+        ObjData::load_buf_with_config(output.as_bytes(), preserving_config()).unwrap(),
+        obj
+    );
+}
+
+#[test]
+fn discard_lines_by_default() {
+    let config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
+    let obj = ObjData::load_buf_with_config(ANNOTATED_QUADS.as_bytes(), config).unwrap();
+
+    assert_eq!(obj.header, None);
+    assert!(obj.objects[0].preserved.is_empty());
+    assert!(obj.objects[0].groups.iter().all(|g| g.preserved.is_empty()));
+
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("# Generated by the obj Rust library"));
+}
@@ -0,0 +1,136 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IndexTuple, LoadConfig, ObjData, ObjError, ObjReader, Statement, Warning};
+
+static SQUARES: &str = "
+mtllib squares.mtl
+o squares
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+g first
+usemtl red
+f 1/1 2/0 3
+g second third
+f -4 -3 -2 -1
+";
+
+#[test]
+fn read_statements() {
+    let mut reader = ObjReader::new(SQUARES.as_bytes(), LoadConfig::default());
+    let mut faces = Vec::new();
+    let mut names = Vec::new();
+    let mut vertices = 0;
+    while let Some((line_number, statement)) = reader.next_statement() {
+        match statement.unwrap() {
+            Statement::Vertex { position, w, color } => {
+                assert_eq!((w, color), (None, None));
+                assert_eq!(position[2], 0.0);
+                vertices += 1;
+            }
+            Statement::Face(indices) => faces.push((line_number, indices.to_vec())),
+            Statement::MtlLib(name) | Statement::Object(name) | Statement::Group(name) => names.push(name.to_string()),
+            Statement::UseMtl(name) => assert_eq!(name, Some("red")),
+            Statement::TexCoord { .. } => {}
+            other => panic!("unexpected statement {:?}", other),
+        }
+    }
+
+    assert_eq!(vertices, 4);
+    assert_eq!(names, ["squares.mtl", "squares", "first", "second third"]);
+    assert_eq!(
+        faces,
+        [
+            (
+                11,
+                vec![
+                    IndexTuple(0, Some(0), None),
+                    IndexTuple(1, None, None),
+                    IndexTuple(2, None, None),
+                ]
+            ),
+            (
+                13,
+                vec![
+                    IndexTuple(0, None, None),
+                    IndexTuple(1, None, None),
+                    IndexTuple(2, None, None),
+                    IndexTuple(3, None, None),
+                ]
+            ),
+        ]
+    );
+    assert_eq!(
+        reader.warnings().collect::<Vec<_>>(),
+        [Warning::ZeroTextureIndex { line_number: 11 }]
+    );
+}
+
+#[test]
+fn matches_loader() {
+    let obj = ObjData::load_buf(SQUARES.as_bytes()).unwrap();
+    let mut reader = ObjReader::new(SQUARES.as_bytes(), LoadConfig::default());
+    let mut polys = obj.objects[0].groups.iter().flat_map(|g| &g.polys);
+    while let Some((_, statement)) = reader.next_statement() {
+        if let Statement::Face(indices) = statement.unwrap() {
+            assert_eq!(indices, polys.next().unwrap().0.as_slice());
+        }
+    }
+    assert!(polys.next().is_none());
+}
+
+#[test]
+fn continue_after_error() {
+    let input = "v 0 0 0\nf 1 2\np 1\n";
+    let mut reader = ObjReader::new(input.as_bytes(), LoadConfig::default());
+
+    let (_, statement) = reader.next_statement().unwrap();
+    assert!(matches!(statement, Ok(Statement::Vertex { .. })));
+
+    let (line_number, statement) = reader.next_statement().unwrap();
+    let err = statement.unwrap_err();
+    assert_eq!(line_number, 2);
+    assert!(matches!(err.kind(), ObjError::IndexOutOfRange { index: 2, .. }));
+    assert_eq!(err.location().unwrap().span, 4..5);
+    assert_eq!(reader.line(), "f 1 2");
+
+    let (_, statement) = reader.next_statement().unwrap();
+    assert_eq!(statement.unwrap(), Statement::Point(&[0]));
+    assert!(reader.next_statement().is_none());
+}
+
+#[test]
+fn forward_references() {
+    let config = LoadConfig {
+        forward_references: true,
+        ..LoadConfig::default()
+    };
+    let mut reader = ObjReader::new("p 1 3\nv 0 0 0\nv 1 0 0\n".as_bytes(), config);
+    while let Some((_, statement)) = reader.next_statement() {
+        statement.unwrap();
+    }
+    let errors = reader.forward_reference_errors();
+    assert!(matches!(
+        errors.as_slice(),
+        [ObjError::IndexOutOfRange {
+            line_number: 1,
+            index: 3,
+            len: 2,
+            ..
+        }]
+    ));
+}
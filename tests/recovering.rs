@@ -0,0 +1,90 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{LoadConfig, Mtl, MtlError, ObjData, ObjError};
+
+static BROKEN_QUADS: &str = "
+v 0 0 0
+v 1 0 x
+v 1 0 0
+v 1 1 0
+vt 0 0
+f 1 2 3
+f 0 1 2
+scale 2
+o quads
+f 1/1 2 3
+";
+
+#[test]
+fn load_recovering() {
+    let (obj, errors) = ObjData::load_buf_recovering(BROKEN_QUADS.as_bytes(), LoadConfig::default());
+
+    assert_eq!(obj.position.len(), 3);
+    assert_eq!(obj.objects.len(), 2);
+    assert_eq!(obj.objects[0].groups[0].polys.len(), 1);
+    assert_eq!(obj.objects[1].name, "quads");
+    assert_eq!(obj.objects[1].groups[0].polys.len(), 1);
+
+    assert_eq!(errors.len(), 3);
+    assert!(matches!(
+        errors[0].kind(),
+        ObjError::ArgumentListFailure { line_number: 3, .. }
+    ));
+    assert!(matches!(
+        errors[1].kind(),
+        ObjError::ZeroVertexNumber { line_number: 8 }
+    ));
+    assert!(matches!(
+        errors[2].kind(),
+        ObjError::UnexpectedCommand { line_number: 9, .. }
+    ));
+
+    // The first error is returned when not recovering.
+    assert!(matches!(
+        ObjData::load_buf(BROKEN_QUADS.as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 3, .. }
+    ));
+}
+
+#[test]
+fn load_valid_recovering() {
+    let input = BROKEN_QUADS
+        .replace("1 0 x", "1 0 0")
+        .replace("f 0", "f 1")
+        .replace("scale 2", "");
+    let (obj, errors) = ObjData::load_buf_recovering(input.as_bytes(), LoadConfig::default());
+
+    assert!(errors.is_empty());
+    assert_eq!(obj, ObjData::load_buf(input.as_bytes()).unwrap());
+}
+
+#[test]
+fn reload_mtl_recovering() {
+    let input = "newmtl red\nKd 1 0\nKa 1 0 0\nfoo\nnewmtl\nNs 2\nnewmtl blue\nKd 0 0 1\n";
+    let mut mtl = Mtl::new("broken.mtl".to_string());
+    let errors = mtl.reload_recovering(input.as_bytes());
+
+    assert_eq!(errors.len(), 3);
+    assert!(matches!(errors[0].kind(), MtlError::InvalidValue(_)));
+    assert!(matches!(errors[1].kind(), MtlError::InvalidInstruction(s) if s == "foo"));
+    assert!(matches!(errors[2].kind(), MtlError::MissingMaterialName));
+    assert_eq!(errors[1].location().map(|l| l.line_number), Some(4));
+
+    let names: Vec<_> = mtl.materials.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["red", "blue"]);
+    assert_eq!(mtl.materials[0].ka, Some([1.0, 0.0, 0.0]));
+    assert_eq!(mtl.materials[0].kd, None);
+    assert_eq!(mtl.materials[1].kd, Some([0.0, 0.0, 1.0]));
+}
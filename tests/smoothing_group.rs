@@ -0,0 +1,78 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{LoadConfig, ObjData, Warning};
+
+fn smoothing_groups(obj: &ObjData) -> Vec<(&str, usize, Option<u32>, usize)> {
+    obj.objects[0]
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.index, g.smoothing_group, g.polys.len()))
+        .collect()
+}
+
+#[test]
+fn smoothing_group_changes_split_groups() {
+    let input = "v 0 0 0\ng quads\nf 1 1 1\ns 1\nf 1 1 1\ns 1\nf 1 1 1\ns 2\nf 1 1 1\ng other\nf 1 1 1\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    // Repeating the current group number doesn't split the group, and `g` keeps the smoothing group.
+    assert_eq!(
+        smoothing_groups(&obj),
+        vec![
+            ("quads", 0, None, 1),
+            ("quads", 1, Some(1), 2),
+            ("quads", 2, Some(2), 1),
+            ("other", 0, Some(2), 1),
+        ]
+    );
+}
+
+#[test]
+fn smoothing_off() {
+    let input = "v 0 0 0\ns 1\nf 1 1 1\ns off\nf 1 1 1\ns 0\nf 1 1 1\n";
+    let obj = ObjData::load_buf(input.as_bytes()).unwrap();
+
+    // `s 0` is the same as `s off`, which is how it is written back.
+    assert_eq!(
+        smoothing_groups(&obj),
+        vec![("default", 0, Some(1), 1), ("default", 1, Some(0), 2)]
+    );
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.ends_with("s 1\nf 1 1 1\ns off\nf 1 1 1\nf 1 1 1\n"),
+        "{}",
+        output
+    );
+    assert_eq!(ObjData::load_buf(output.as_bytes()).unwrap(), obj);
+}
+
+#[test]
+fn load_malformed_smoothing_group() {
+    let input = "v 0 0 0\ns 1\nf 1 1 1\ns\nf 1 1 1\ns 1.0\ns on\ns 2 # comment\nf 1 1 1\ns 3 4\nf 1 1 1\n";
+    let (obj, warnings) = ObjData::load_buf_with_warnings(input.as_bytes(), LoadConfig::default()).unwrap();
+
+    let groups: Vec<_> = obj.objects[0].groups.iter().map(|g| g.smoothing_group).collect();
+    assert_eq!(groups, vec![Some(1), Some(0), Some(2), Some(0)]);
+    let lines: Vec<_> = warnings
+        .iter()
+        .map(|warning| match warning {
+            Warning::MalformedSmoothingGroup { line_number, args } => (*line_number, args.as_str()),
+            other => panic!("expected a malformed smoothing group, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(lines, vec![(4, ""), (6, "1.0"), (7, "on"), (10, "3 4")]);
+}
@@ -0,0 +1,205 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IndexTuple, ObjData, ObjError};
+
+static BILINEAR_PATCH: &str = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 1
+vt 0 0
+vt 1 0
+vt 0 1
+vt 1 1
+
+cstype bezier
+deg 1 1
+stech cparma 2 2
+surf 0 1 0 1 1/1 2/2 3/3 4/4
+parm u 0 1
+parm v 0 1
+end
+";
+
+static QUARTER_CYLINDER: &str = "
+v 1 0 0
+v 1 1 0 0.70710678
+v 0 1 0
+v 1 0 1
+v 1 1 1 0.70710678
+v 0 1 1
+
+cstype rat bezier
+deg 2 1
+stech cparmb 4
+surf 0 1 0 1 1 2 3 4 5 6
+parm u 0 1
+parm v 0 1
+end
+";
+
+static TRIMMED_PLANE: &str = "
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vp 0.2 0.2
+vp 0.8 0.2
+vp 0.8 0.8
+vp 0.2 0.8
+vp 0.2 0.2
+vp 0.4 0.4
+vp 0.6 0.4
+vp 0.6 0.6
+vp 0.4 0.6
+vp 0.4 0.4
+
+cstype bspline
+deg 1
+curv2 1 2 3 4 5
+parm u 0 0 1 2 3 4 4
+end
+curv2 6 7 8 9 10
+parm u 0 0 1 2 3 4 4
+end
+
+cstype bezier
+deg 1 1
+stech cparma 20 20
+surf 0 1 0 1 1 2 3 4
+parm u 0 1
+parm v 0 1
+trim 0 4 1
+hole 0 4 2
+end
+";
+
+#[test]
+fn tessellate_bilinear_patch() {
+    let mut obj = ObjData::load_buf(BILINEAR_PATCH.as_bytes()).unwrap();
+    let group = obj.tessellate_surfaces("patch".to_string()).unwrap();
+
+    assert_eq!(group.name, "patch");
+    assert_eq!(group.polys.len(), 8);
+    assert_eq!(obj.position.len(), 4 + 9);
+    assert_eq!(obj.texture.len(), 4 + 9);
+    assert_eq!(obj.normal.len(), 9);
+
+    assert_eq!(group.polys[0].0[0], IndexTuple(4, Some(4), Some(0)));
+    assert_eq!(obj.position[4], [0., 0., 0.]);
+    assert!(obj.position[4..].contains(&[0.5, 0.5, 0.25]));
+    assert!(obj.position[4..].contains(&[1., 1., 1.]));
+    for poly in &group.polys {
+        assert_eq!(poly.0.len(), 3);
+        for &IndexTuple(p, t, n) in &poly.0 {
+            let [x, y, _] = obj.position[p];
+            assert_eq!(obj.texture[t.unwrap()], [x, y]);
+            assert!(obj.normal[n.unwrap()][2] > 0.0);
+        }
+    }
+}
+
+#[test]
+fn tessellate_rational_surface() {
+    let mut obj = ObjData::load_buf(QUARTER_CYLINDER.as_bytes()).unwrap();
+    let group = obj.tessellate_surfaces("cylinder".to_string()).unwrap();
+
+    assert_eq!(group.polys.len(), 4 * 4 * 2);
+    assert_eq!(obj.position.len(), 6 + 25);
+    for &[x, y, _] in &obj.position[6..] {
+        assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn tessellate_trimmed_surface() {
+    let mut obj = ObjData::load_buf(TRIMMED_PLANE.as_bytes()).unwrap();
+    let group = obj.tessellate_surfaces("trimmed".to_string()).unwrap();
+
+    assert!(!group.polys.is_empty());
+    assert!(group.polys.len() < 20 * 20 * 2);
+    for poly in &group.polys {
+        let mut center = [0.0; 2];
+        for IndexTuple(p, _, _) in &poly.0 {
+            center[0] += obj.position[*p][0] / 3.0;
+            center[1] += obj.position[*p][1] / 3.0;
+        }
+        assert!(center.iter().all(|&c| c > 0.2 && c < 0.8));
+        assert!(!center.iter().all(|&c| c > 0.4 && c < 0.6));
+    }
+}
+
+#[test]
+fn tessellate_wrong_vertex_count() {
+    let input = BILINEAR_PATCH.replace("1/1 2/2 3/3 4/4", "1 2 3");
+    let mut obj = ObjData::load_buf(input.as_bytes()).unwrap();
+    match obj.tessellate_surfaces("patch".to_string()) {
+        Err(ObjError::TessellationFailure { surface: 0, .. }) => {}
+        other => panic!("expected a tessellation failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn tessellate_failure_appends_nothing() {
+    let broken = BILINEAR_PATCH.replace("surf 0 1 0 1 1/1 2/2 3/3 4/4", "surf 0 1 0 1 1 2 3");
+    let input = format!("{}{}", BILINEAR_PATCH, &broken[broken.find("cstype").unwrap()..]);
+    let mut obj = ObjData::load_buf(input.as_bytes()).unwrap();
+    assert_eq!(obj.free_form.surfaces.len(), 2);
+
+    let before = obj.clone();
+    match obj.tessellate_surfaces("patch".to_string()) {
+        Err(ObjError::TessellationFailure { surface: 1, .. }) => {}
+        other => panic!("expected a tessellation failure, got {:?}", other),
+    }
+    assert_eq!(obj, before);
+}
+
+#[test]
+fn tessellate_too_many_vertices() {
+    let mut input = String::new();
+    for j in 0..5 {
+        for i in 0..5 {
+            input.push_str(&format!("v {} {} 0\n", i, j));
+        }
+    }
+    let vertices: Vec<_> = (1..=25).map(|i| i.to_string()).collect();
+    input.push_str(&format!(
+        "cstype bezier\ndeg 1 1\nstech cparma 1024 1024\nsurf 0 4 0 4 {}\nparm u 0 1 2 3 4\nparm v 0 1 2 3 4\nend\n",
+        vertices.join(" ")
+    ));
+    let mut obj = ObjData::load_buf(input.as_bytes()).unwrap();
+    match obj.tessellate_surfaces("patch".to_string()) {
+        Err(ObjError::TessellationFailure { surface: 0, message }) if message.contains("in total") => {}
+        other => panic!("expected a tessellation failure, got {:?}", other),
+    }
+    assert_eq!(obj.position.len(), 25);
+}
+
+#[test]
+fn tessellate_unreasonable_degree_and_step() {
+    let huge_degree = BILINEAR_PATCH.replace("deg 1 1", "deg 18446744073709551615 1");
+    let huge_bezier = BILINEAR_PATCH.replace("deg 1 1", "deg 200000 200000");
+    let huge_step = BILINEAR_PATCH.replace(
+        "cstype bezier\ndeg 1 1",
+        "cstype bmatrix\ndeg 1 1\nbmat u 1 -1 0 1\nbmat v 1 -1 0 1\nstep 1e30 1",
+    );
+    for input in &[huge_degree, huge_bezier, huge_step] {
+        let mut obj = ObjData::load_buf(input.as_bytes()).unwrap();
+        match obj.tessellate_surfaces("patch".to_string()) {
+            Err(ObjError::TessellationFailure { surface: 0, message }) => assert!(message.contains("too")),
+            other => panic!("expected a tessellation failure, got {:?}", other),
+        }
+    }
+}
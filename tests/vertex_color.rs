@@ -0,0 +1,90 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{LoadConfig, ObjData, ObjError, Warning};
+
+#[test]
+fn vertex_colors() {
+    let obj = ObjData::load_buf("v 0 0 0 1 0 0\nv 1 0 0 0.5 0 1 0\n".as_bytes()).unwrap();
+
+    assert_eq!(obj.position, vec![[0., 0., 0.], [1., 0., 0.]]);
+    assert_eq!(obj.position_w, vec![1., 0.5]);
+    assert_eq!(obj.color, vec![[1., 0., 0.], [0., 1., 0.]]);
+
+    // Colors follow the weight, which is written for all vertices once any has one.
+    let mut output = Vec::new();
+    obj.write_to_buf(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("v 0 0 0 1 1 0 0\nv 1 0 0 0.5 0 1 0\n"), "{}", output);
+    assert_eq!(ObjData::load_buf(output.as_bytes()).unwrap(), obj);
+}
+
+#[test]
+fn no_vertex_colors() {
+    let obj = ObjData::load_buf("v 0 0 0\nv 1 0 0\n".as_bytes()).unwrap();
+
+    assert_eq!(obj.position.len(), 2);
+    assert!(obj.color.is_empty());
+}
+
+#[test]
+fn inconsistent_vertex_colors() {
+    let missing = "v 0 0 0 1 0 0\nv 1 0 0\n";
+    assert!(matches!(
+        ObjData::load_buf(missing.as_bytes()).unwrap_err().kind(),
+        ObjError::InconsistentVertexColors { line_number: 2 }
+    ));
+
+    let unexpected = "v 0 0 0\nv 1 0 0 1 0 0\n";
+    assert!(matches!(
+        ObjData::load_buf(unexpected.as_bytes()).unwrap_err().kind(),
+        ObjError::InconsistentVertexColors { line_number: 2 }
+    ));
+
+    let partial = "v 0 0 0 1 0\n";
+    assert!(matches!(
+        ObjData::load_buf(partial.as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 1, .. }
+    ));
+}
+
+#[test]
+fn extra_vertex_arguments() {
+    let input = "v 0 0 0 1 0\nv 1 0 0 1 x y\nv 0 1 0 1 # comment\n";
+    assert!(matches!(
+        ObjData::load_buf(input.as_bytes()).unwrap_err().kind(),
+        ObjError::ArgumentListFailure { line_number: 1, .. }
+    ));
+
+    let config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
+    let (obj, warnings) = ObjData::load_buf_with_warnings(input.as_bytes(), config).unwrap();
+    assert_eq!(obj.position, vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+    assert!(obj.color.is_empty());
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::ExtraVertexArguments {
+                line_number: 1,
+                args: "0".to_string(),
+            },
+            Warning::ExtraVertexArguments {
+                line_number: 2,
+                args: "x y".to_string(),
+            },
+        ]
+    );
+}
@@ -0,0 +1,134 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{FaceVertex, IndexTuple, LoadConfig, ObjError, ObjReader, Visitor, Warning};
+
+static SQUARE: &str = "
+v 0 0 0
+v 2 0 0
+v 2 2 0
+v 0 2 0
+vt 0 0
+vn 0 0 1
+g square
+usemtl red
+f 1/1/1 2//1 3/0/1
+f -4 -2 -1
+";
+
+/// Accumulates statistics of the faces without keeping them.
+#[derive(Default)]
+struct Stats {
+    vertices: usize,
+    faces: usize,
+    area: f32,
+    groups: Vec<String>,
+    materials: Vec<Option<String>>,
+    warnings: Vec<Warning>,
+    first: Option<FaceVertex>,
+}
+
+impl Visitor for Stats {
+    fn vertex(&mut self, _position: [f32; 3]) {
+        self.vertices += 1;
+    }
+
+    fn face(&mut self, face: &[FaceVertex]) {
+        self.faces += 1;
+        self.first.get_or_insert(face[0]);
+        // Area of a triangle in the z = 0 plane.
+        let [a, b, c] = [face[0].position, face[1].position, face[2].position];
+        self.area += ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.0;
+    }
+
+    fn group(&mut self, names: &str) {
+        self.groups.push(names.to_string());
+    }
+
+    fn material(&mut self, name: Option<&str>) {
+        self.materials.push(name.map(String::from));
+    }
+
+    fn warning(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+}
+
+#[test]
+fn visit_faces() {
+    let mut stats = Stats::default();
+    ObjReader::new(SQUARE.as_bytes(), LoadConfig::default())
+        .visit(&mut stats)
+        .unwrap();
+
+    assert_eq!(stats.vertices, 4);
+    assert_eq!(stats.faces, 2);
+    assert_eq!(stats.area, 4.0);
+    assert_eq!(stats.groups, ["square"]);
+    assert_eq!(stats.materials, [Some("red".to_string())]);
+    assert_eq!(stats.warnings, [Warning::ZeroTextureIndex { line_number: 10 }]);
+    assert_eq!(
+        stats.first,
+        Some(FaceVertex {
+            index: IndexTuple(0, Some(0), Some(0)),
+            position: [0.0, 0.0, 0.0],
+            texture: Some([0.0, 0.0]),
+            normal: Some([0.0, 0.0, 1.0]),
+        })
+    );
+}
+
+#[test]
+fn visit_rejects_forward_references() {
+    let config = LoadConfig {
+        forward_references: true,
+        ..LoadConfig::default()
+    };
+    let err = ObjReader::new("v 0 0 0\nv 1 0 0\nf 1 2 3\nv 0 1 0\n".as_bytes(), config)
+        .visit(&mut Stats::default())
+        .unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ObjError::IndexOutOfRange {
+            line_number: 3,
+            index: 3,
+            len: 2,
+            ..
+        }
+    ));
+    assert_eq!(err.location().unwrap().span, 6..7);
+}
+
+#[test]
+fn visit_unknown_commands() {
+    let input = "v 0 0 0\nfoo bar\n";
+    let err = ObjReader::new(input.as_bytes(), LoadConfig::default())
+        .visit(&mut Stats::default())
+        .unwrap_err();
+    assert!(matches!(err.kind(), ObjError::UnexpectedCommand { line_number: 2, .. }));
+
+    let config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
+    let mut stats = Stats::default();
+    ObjReader::new(input.as_bytes(), config).visit(&mut stats).unwrap();
+    assert_eq!(
+        stats.warnings,
+        [Warning::UnknownCommand {
+            line_number: 2,
+            command: "foo".to_string()
+        }]
+    );
+}
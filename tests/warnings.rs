@@ -0,0 +1,139 @@
+//   Copyright 2017 GFX Developers
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+
+use obj::{IncludePolicy, IndexTuple, LoadConfig, Obj, ObjData, ObjMaterial, Warning};
+use std::io;
+use std::path::PathBuf;
+
+static SQUARE: &str = "
+mtllib square.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+f 1/0/1 2/1/0 3/1/1
+adjf 0 1
+usemtl red
+f 1/1/1 3/1/1 4/1/1
+g back
+usemtl blue
+f 1 4 3
+";
+
+static SQUARE_MTL: &str = "
+Kd 1 1 1
+newmtl red
+Kd 1 0 0
+";
+
+#[test]
+fn load_with_warnings() {
+    let config = LoadConfig {
+        strict: false,
+        ..LoadConfig::default()
+    };
+    let (obj, warnings) = ObjData::load_buf_with_warnings(SQUARE.as_bytes(), config).unwrap();
+
+    assert_eq!(obj, ObjData::load_buf_with_config(SQUARE.as_bytes(), config).unwrap());
+    assert_eq!(obj.objects[0].groups[0].polys[0].0[0], IndexTuple(0, None, Some(0)));
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::ZeroTextureIndex { line_number: 9 },
+            Warning::ZeroNormalIndex { line_number: 9 },
+            Warning::UnknownCommand {
+                line_number: 10,
+                command: "adjf".to_string(),
+            },
+        ]
+    );
+
+    // Warnings are sent to the callback as they are found.
+    let mut count = 0;
+    ObjData::load_buf_with_warning_fn(SQUARE.as_bytes(), config, |_| count += 1).unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn load_called_with_warnings() {
+    let dir = std::env::temp_dir().join("obj_load_called_with_warnings");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("quad.obj"), "v 0 0 0\nv 1 0 0\ncall tri.obj\n").unwrap();
+    std::fs::write(dir.join("tri.obj"), "v 1 1 0\nv 0 1 0\nvt 0 0\nf 1/0 2/1 -1/1\n").unwrap();
+
+    let config = LoadConfig {
+        include: IncludePolicy::Resolve,
+        ..LoadConfig::default()
+    };
+    let (obj, warnings) = Obj::load_with_warnings(dir.join("quad.obj"), config).unwrap();
+
+    assert_eq!(obj.data.objects[0].groups[0].polys.len(), 1);
+    assert_eq!(
+        warnings,
+        vec![Warning::Called {
+            line_number: 3,
+            filename: "tri.obj".to_string(),
+            warning: Box::new(Warning::ZeroTextureIndex { line_number: 4 }),
+        }]
+    );
+}
+
+#[test]
+fn load_mtls_with_warnings() {
+    let data = ObjData::load_buf_with_config(
+        SQUARE.as_bytes(),
+        LoadConfig {
+            strict: false,
+            ..LoadConfig::default()
+        },
+    )
+    .unwrap();
+    let mut obj = Obj {
+        data,
+        path: PathBuf::new(),
+    };
+
+    let mut warnings = Vec::new();
+    obj.load_mtls_with_warning_fn(
+        |_, mtllib| match mtllib {
+            "square.mtl" => Ok(SQUARE_MTL.as_bytes()),
+            _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+        },
+        |w| warnings.push(w),
+    )
+    .unwrap();
+
+    let groups = &obj.data.objects[0].groups;
+    assert!(matches!(groups[1].material, Some(ObjMaterial::Mtl(ref m)) if m.name == "red"));
+    assert!(matches!(groups[2].material, Some(ObjMaterial::Ref(ref m)) if m == "blue"));
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::MaterialLibrary {
+                filename: "square.mtl".to_string(),
+                warning: Box::new(Warning::StatementOutsideMaterial {
+                    line_number: 2,
+                    command: "Kd".to_string(),
+                }),
+            },
+            Warning::UnresolvedMaterial {
+                object: "default".to_string(),
+                group: "back".to_string(),
+                material: "blue".to_string(),
+            },
+        ]
+    );
+}